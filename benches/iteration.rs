@@ -0,0 +1,147 @@
+//! Compares the cost of iterating over a generator with that of a
+//! hand-written iterator. Each value is passed through `black_box` so that the
+//! compiler can't replace the whole loop with a closed-form sum.
+//!
+//! Run with `cargo bench --bench iteration`. When run without `--bench` (e.g.
+//! by `cargo test --all-targets`) each benchmark only runs once as a smoke
+//! test.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use fauxgen::{gen, GeneratorToken};
+
+const COUNT: u64 = 10_000;
+
+#[fauxgen::generator(yield = u64)]
+fn numbers(count: u64) {
+    for i in 0..count {
+        r#yield!(i);
+    }
+}
+
+#[fauxgen::generator(yield = u64)]
+fn batched(count: u64) {
+    const BATCH: u64 = 1024;
+
+    for start in (0..count).step_by(BATCH as usize) {
+        yield_batch!(start..count.min(start + BATCH));
+    }
+}
+
+struct Numbers {
+    current: u64,
+    count: u64,
+}
+
+impl Iterator for Numbers {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == self.count {
+            return None;
+        }
+
+        self.current += 1;
+        Some(self.current - 1)
+    }
+}
+
+fn hand_written_next() -> u64 {
+    let iter = Numbers {
+        current: 0,
+        count: black_box(COUNT),
+    };
+
+    let mut sum = 0;
+    // A for loop calls next for each value, unlike the consuming iterator methods.
+    for value in iter {
+        sum += black_box(value);
+    }
+    sum
+}
+
+fn hand_written_sum() -> u64 {
+    let iter = Numbers {
+        current: 0,
+        count: black_box(COUNT),
+    };
+
+    iter.map(black_box).sum()
+}
+
+fn generator_next() -> u64 {
+    let gen = std::pin::pin!(numbers(black_box(COUNT)));
+
+    let mut sum = 0;
+    for value in gen {
+        sum += black_box(value);
+    }
+    sum
+}
+
+fn generator_sum() -> u64 {
+    let gen = std::pin::pin!(numbers(black_box(COUNT)));
+
+    gen.map(black_box).sum()
+}
+
+fn inline_sum() -> u64 {
+    let count = black_box(COUNT);
+    let gen = std::pin::pin!(gen!(|token: GeneratorToken<u64>| {
+        for i in 0..count {
+            token.yield_(i).await;
+        }
+    }));
+
+    gen.map(black_box).sum()
+}
+
+fn batched_next() -> u64 {
+    let gen = std::pin::pin!(batched(black_box(COUNT)));
+
+    let mut sum = 0;
+    for value in gen {
+        sum += black_box(value);
+    }
+    sum
+}
+
+fn batched_sum() -> u64 {
+    let gen = std::pin::pin!(batched(black_box(COUNT)));
+
+    gen.map(black_box).sum()
+}
+
+fn bench(name: &str, iterations: u32, func: fn() -> u64) {
+    const SAMPLES: u32 = 10;
+
+    // Take the fastest sample to reduce the noise from other processes.
+    let mut best = f64::INFINITY;
+    for _ in 0..SAMPLES {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert_eq!(black_box(func()), COUNT * (COUNT - 1) / 2);
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+
+        best = best.min(elapsed * 1e9 / f64::from(iterations) / COUNT as f64);
+    }
+
+    println!("{name:<20} {best:>8.2} ns per item");
+}
+
+fn main() {
+    let iterations = match std::env::args().any(|arg| arg == "--bench") {
+        true => 200,
+        false => 1,
+    };
+
+    bench("hand-written next", iterations, hand_written_next);
+    bench("hand-written sum", iterations, hand_written_sum);
+    bench("generator next", iterations, generator_next);
+    bench("generator sum", iterations, generator_sum);
+    bench("inline sum", iterations, inline_sum);
+    bench("batched next", iterations, batched_next);
+    bench("batched sum", iterations, batched_sum);
+}
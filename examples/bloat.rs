@@ -0,0 +1,56 @@
+//! A binary with many distinct generator types, used to keep track of how much
+//! code each generator adds.
+//!
+//! Each generator here is instantiated with its own yield type so every one of
+//! them gets its own copy of the generic parts of fauxgen. To see how much
+//! code that comes to, run
+//!
+//! ```text
+//! cargo bloat --release --example bloat --filter fauxgen -n 20
+//! ```
+//!
+//! using [`cargo-bloat`](https://github.com/RazrFalcon/cargo-bloat). The
+//! total size of the fauxgen functions divided by the number of generators
+//! here is a rough measure of the per-generator cost.
+
+use std::pin::pin;
+
+use fauxgen::generator;
+
+macro_rules! generators {
+    ($( $name:ident => $ty:ident ),* $(,)?) => {
+        $(
+            #[derive(Debug)]
+            struct $ty(u32);
+
+            #[generator(yield = $ty)]
+            fn $name(count: u32) {
+                for i in 0..count {
+                    r#yield!($ty(i));
+                }
+            }
+        )*
+
+        fn main() {
+            let count = std::env::args().count() as u32 + 2;
+
+            $(
+                let mut gen = pin!($name(count));
+                let first = gen.next();
+                let rest: u32 = gen.as_mut().map(|value| value.0).sum();
+                println!("{:?} {:?} {}", gen, first, rest);
+            )*
+        }
+    };
+}
+
+generators! {
+    gen00 => Value00, gen01 => Value01, gen02 => Value02, gen03 => Value03,
+    gen04 => Value04, gen05 => Value05, gen06 => Value06, gen07 => Value07,
+    gen08 => Value08, gen09 => Value09, gen10 => Value10, gen11 => Value11,
+    gen12 => Value12, gen13 => Value13, gen14 => Value14, gen15 => Value15,
+    gen16 => Value16, gen17 => Value17, gen18 => Value18, gen19 => Value19,
+    gen20 => Value20, gen21 => Value21, gen22 => Value22, gen23 => Value23,
+    gen24 => Value24, gen25 => Value25, gen26 => Value26, gen27 => Value27,
+    gen28 => Value28, gen29 => Value29, gen30 => Value30, gen31 => Value31,
+}
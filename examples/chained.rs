@@ -1,6 +1,6 @@
 use std::pin::Pin;
 
-use fauxgen::{generator, Generator, GeneratorIter, GeneratorState};
+use fauxgen::{generator, Generator, GeneratorIter, GeneratorState, YieldHint};
 use pin_project::pin_project;
 
 #[pin_project]
@@ -37,6 +37,17 @@ where
     }
 }
 
+// The chain yields at most one value for each value of the first generator, but
+// stops early if the second generator completes.
+impl<G1, G2> YieldHint for ChainedGenerator<G1, G2>
+where
+    G1: YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        (0, self.gen1.yield_hint().1)
+    }
+}
+
 #[generator(yield = u64)]
 fn powers_of_two() {
     let mut value = 1;
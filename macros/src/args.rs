@@ -1,6 +1,7 @@
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 
 pub struct MacroArg<K, V> {
     pub key: K,
@@ -26,10 +27,97 @@ impl<K: ToTokens, V: ToTokens> ToTokens for MacroArg<K, V> {
     }
 }
 
+/// The checks that can be turned off with `allow(...)`.
+pub const LINTS: &[&str] = &["foreign_await", "late_argument"];
+
+/// `allow(lint, ...)`
+pub struct AllowArg {
+    pub key: syn::Ident,
+    pub paren_token: syn::token::Paren,
+    pub lints: Punctuated<syn::Ident, syn::Token![,]>,
+}
+
+impl Parse for AllowArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let arg = Self {
+            key: input.parse()?,
+            paren_token: syn::parenthesized!(content in input),
+            lints: Punctuated::parse_terminated(&content)?,
+        };
+
+        for lint in &arg.lints {
+            if !LINTS.iter().any(|name| lint == name) {
+                return Err(syn::Error::new_spanned(
+                    lint,
+                    format!("unknown lint `{lint}`"),
+                ));
+            }
+        }
+
+        Ok(arg)
+    }
+}
+
+impl ToTokens for AllowArg {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.key.to_tokens(tokens);
+        self.paren_token
+            .surround(tokens, |tokens| self.lints.to_tokens(tokens));
+    }
+}
+
+/// `introspect` or `introspect(counters)`
+pub struct IntrospectArg {
+    pub key: syn::Ident,
+    pub options: Option<(syn::token::Paren, syn::Ident)>,
+}
+
+impl IntrospectArg {
+    /// Whether the yield sites keep counters.
+    pub fn counters(&self) -> bool {
+        self.options.is_some()
+    }
+}
+
+impl Parse for IntrospectArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        if !input.peek(syn::token::Paren) {
+            return Ok(Self { key, options: None });
+        }
+
+        let content;
+        let paren_token = syn::parenthesized!(content in input);
+        let option: syn::Ident = content.parse()?;
+        if option != "counters" {
+            return Err(syn::Error::new_spanned(
+                &option,
+                format!("unknown introspect option `{option}`"),
+            ));
+        }
+
+        Ok(Self {
+            key,
+            options: Some((paren_token, option)),
+        })
+    }
+}
+
+impl ToTokens for IntrospectArg {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.key.to_tokens(tokens);
+        if let Some((paren_token, option)) = &self.options {
+            paren_token.surround(tokens, |tokens| option.to_tokens(tokens));
+        }
+    }
+}
+
 pub enum ArgName {
     Ident(syn::Ident),
     Yield(syn::Token![yield]),
     Crate(syn::Token![crate]),
+    Try(syn::Token![try]),
 }
 
 impl Parse for ArgName {
@@ -40,6 +128,7 @@ impl Parse for ArgName {
             _ if lookahead.peek(syn::Ident) => Ok(Self::Ident(input.parse()?)),
             _ if lookahead.peek(syn::Token![yield]) => Ok(Self::Yield(input.parse()?)),
             _ if lookahead.peek(syn::Token![crate]) => Ok(Self::Crate(input.parse()?)),
+            _ if lookahead.peek(syn::Token![try]) => Ok(Self::Try(input.parse()?)),
             _ => Err(lookahead.error()),
         }
     }
@@ -49,47 +138,118 @@ pub struct Args {
     pub crate_: Option<MacroArg<syn::Token![crate], syn::Path>>,
     pub yield_: Option<MacroArg<syn::Token![yield], Box<syn::Type>>>,
     pub arg: Option<MacroArg<syn::Ident, Box<syn::Type>>>,
+    pub recursive: Option<syn::Ident>,
+    pub size_hint: Option<MacroArg<syn::Ident, Box<syn::Expr>>>,
+    pub allow: Option<AllowArg>,
+    pub try_: Option<syn::Token![try]>,
+    pub maybe_async: Option<syn::Ident>,
+    pub max_size: Option<MacroArg<syn::Ident, Box<syn::Expr>>>,
+    pub boxed_state: Option<syn::Ident>,
+    pub introspect: Option<IntrospectArg>,
+    pub send: Option<syn::Ident>,
+}
+
+impl Args {
+    /// The path to the fauxgen crate.
+    pub fn krate(&self) -> syn::Path {
+        match &self.crate_ {
+            Some(krate) => krate.value.clone(),
+            None => syn::parse_quote!(::fauxgen),
+        }
+    }
+
+    /// The type yielded by the generator.
+    pub fn yield_ty(&self) -> Box<syn::Type> {
+        match &self.yield_ {
+            Some(ty) => ty.value.clone(),
+            None => syn::parse_quote!(()),
+        }
+    }
+
+    /// The type of the argument passed in when resuming the generator.
+    pub fn arg_ty(&self) -> Box<syn::Type> {
+        match &self.arg {
+            Some(ty) => ty.value.clone(),
+            None => syn::parse_quote!(()),
+        }
+    }
+
+    /// Reject `send`, which only applies to the boxed generators returned by
+    /// `#[into_iter]` and `#[into_stream]` methods.
+    pub fn check_send(&self) -> syn::Result<()> {
+        match &self.send {
+            Some(send) => Err(syn::Error::new_spanned(
+                send,
+                format!("`{send}` is only supported by `#[into_iter]` and `#[into_stream]`"),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether `lint` has been turned off with `allow(...)`.
+    pub fn allows(&self, lint: &str) -> bool {
+        self.allow
+            .as_ref()
+            .is_some_and(|allow| allow.lints.iter().any(|name| name == lint))
+    }
 }
 
 impl Parse for Args {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        Self::parse_until(input, |input| input.is_empty())
+    }
+}
+
+impl Args {
+    /// Parse arguments until `is_end` returns true, leaving the rest of the
+    /// input for the caller.
+    pub fn parse_until(input: ParseStream, is_end: fn(ParseStream) -> bool) -> syn::Result<Self> {
         let mut crate_ = None;
         let mut yield_ = None;
         let mut arg_ = None;
+        let mut recursive = None;
+        let mut size_hint = None;
+        let mut allow = None;
+        let mut try_ = None;
+        let mut maybe_async = None;
+        let mut max_size = None;
+        let mut boxed_state = None;
+        let mut introspect = None;
+        let mut send = None;
 
-        while !input.is_empty() {
+        while !is_end(input) {
             let name: ArgName = input.fork().parse()?;
 
             match name {
-                ArgName::Crate(token) => {
-                    if crate_.replace(input.parse()?).is_some() {
-                        return Err(syn::Error::new_spanned(
-                            token,
-                            format!(
-                                "argument `{}` specified multiple times",
-                                token.to_token_stream()
-                            ),
-                        ));
-                    }
+                ArgName::Crate(token) => set_once(&mut crate_, input.parse()?, token)?,
+                ArgName::Yield(token) => set_once(&mut yield_, input.parse()?, token)?,
+                ArgName::Try(token) => set_once(&mut try_, input.parse()?, token)?,
+                ArgName::Ident(ident) if ident == "arg" => {
+                    set_once(&mut arg_, input.parse()?, ident)?
                 }
-                ArgName::Yield(token) => {
-                    if yield_.replace(input.parse()?).is_some() {
-                        return Err(syn::Error::new_spanned(
-                            token,
-                            format!(
-                                "argument `{}` specified multiple times",
-                                token.to_token_stream()
-                            ),
-                        ));
-                    }
+                ArgName::Ident(ident) if ident == "recursive" => {
+                    set_once(&mut recursive, input.parse()?, ident)?
                 }
-                ArgName::Ident(ident) if ident == "arg" => {
-                    if arg_.replace(input.parse()?).is_some() {
-                        return Err(syn::Error::new_spanned(
-                            ident.clone(),
-                            format!("argument `{ident}` specified multiple times",),
-                        ));
-                    }
+                ArgName::Ident(ident) if ident == "size_hint" => {
+                    set_once(&mut size_hint, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "maybe_async" => {
+                    set_once(&mut maybe_async, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "max_size" => {
+                    set_once(&mut max_size, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "boxed_state" => {
+                    set_once(&mut boxed_state, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "introspect" => {
+                    set_once(&mut introspect, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "send" => {
+                    set_once(&mut send, input.parse()?, ident)?
+                }
+                ArgName::Ident(ident) if ident == "allow" => {
+                    set_once(&mut allow, input.parse()?, ident)?
                 }
                 ArgName::Ident(ident) => {
                     return Err(syn::Error::new_spanned(
@@ -108,6 +268,31 @@ impl Parse for Args {
             crate_,
             yield_,
             arg: arg_,
+            recursive,
+            size_hint,
+            allow,
+            try_,
+            maybe_async,
+            max_size,
+            boxed_state,
+            introspect,
+            send,
         })
     }
 }
+
+/// Store the value of an argument, or report an error at `key` if it has
+/// already been given.
+fn set_once<T>(slot: &mut Option<T>, value: T, key: impl ToTokens) -> syn::Result<()> {
+    if slot.replace(value).is_some() {
+        return Err(syn::Error::new_spanned(
+            &key,
+            format!(
+                "argument `{}` specified multiple times",
+                key.to_token_stream()
+            ),
+        ));
+    }
+
+    Ok(())
+}
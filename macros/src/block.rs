@@ -0,0 +1,159 @@
+use proc_macro2::{Span, TokenStream};
+use syn::parse::{Parse, ParseStream};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::Result;
+
+use crate::args::Args;
+use crate::check::CheckBody;
+use crate::generator::{check_try, info_const, inline_generator, ExpandYield};
+use crate::introspect::YieldSites;
+
+struct GenBlock {
+    asyncness: Option<syn::Token![async]>,
+    args: Args,
+    output: Option<Box<syn::Type>>,
+    body: syn::Block,
+}
+
+impl Parse for GenBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let asyncness = input.parse()?;
+
+        // The body always ends up within an `async move` block, so variables it uses
+        // are moved into the generator whether or not `move` is present.
+        let _movability: Option<syn::Token![move]> = input.parse()?;
+
+        let args = Args::parse_until(input, |input| {
+            input.is_empty() || input.peek(syn::Token![;]) || input.peek(syn::Token![->])
+        })?;
+
+        let output = match input.parse::<Option<syn::Token![->]>>()? {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+
+        let _semi: syn::Token![;] = input.parse()?;
+        let stmts = input.call(syn::Block::parse_within)?;
+
+        Ok(Self {
+            asyncness,
+            args,
+            output,
+            body: syn::Block {
+                brace_token: Default::default(),
+                stmts,
+            },
+        })
+    }
+}
+
+/// The macros that declare a generator from a block.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// `gen_block!`
+    Block,
+
+    /// `try_gen!`, a sync `try` generator whose body evaluates to `()`.
+    TryGen,
+
+    /// `try_stream!`, an async `try` generator whose body evaluates to `()`.
+    TryStream,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Block => "gen_block!",
+            Self::TryGen => "try_gen!",
+            Self::TryStream => "try_stream!",
+        }
+    }
+}
+
+/// Input:
+/// ```ignore
+/// gen_block! { async? move? yield = A, arg = B -> Ret; ... }
+/// ```
+///
+/// Output:
+/// ```ignore
+/// {
+///     const __INFO: GeneratorInfo = ...;
+///     gen_sync(TokenMarker::<A, B>::new(), &__INFO, async move { ... })
+/// }
+/// ```
+///
+/// `try_gen!` and `try_stream!` take the same input, except for `async`, and
+/// create a `try` generator. Their body ends with an implicit `Ok(())`.
+pub fn expand(input: TokenStream, kind: Kind) -> Result<TokenStream> {
+    let GenBlock {
+        asyncness,
+        mut args,
+        output,
+        mut body,
+    } = syn::parse2(input)?;
+    let name = kind.name();
+
+    if let Some(recursive) = &args.recursive {
+        return Err(syn::Error::new_spanned(
+            recursive,
+            format!("`{recursive}` generators cannot be declared with `{name}`"),
+        ));
+    }
+
+    if let Some(maybe_async) = &args.maybe_async {
+        return Err(syn::Error::new_spanned(
+            maybe_async,
+            format!("`{maybe_async}` generators cannot be declared with `{name}`"),
+        ));
+    }
+
+    let is_async = match kind {
+        Kind::Block => asyncness.is_some(),
+        Kind::TryGen | Kind::TryStream => {
+            if let Some(asyncness) = asyncness {
+                return Err(syn::Error::new_spanned(
+                    asyncness,
+                    format!("`{name}` does not take `async`, use `try_stream!` instead"),
+                ));
+            }
+
+            args.try_.get_or_insert_with(Default::default);
+            // The inner block is a statement so the body has to evaluate to `()`.
+            body = syn::parse_quote!({ #body ::core::result::Result::Ok(()) });
+            kind == Kind::TryStream
+        }
+    };
+    check_try(&args)?;
+    args.check_send()?;
+
+    let yield_ident = syn::Ident::new_raw("yield", Span::call_site());
+
+    let mut warnings = Vec::new();
+    ExpandYield::new(yield_ident, &mut warnings).visit_block_mut(&mut body);
+
+    let mut check = CheckBody::new(&args, is_async);
+    check.visit_block(&body);
+    let checks = check.into_diagnostics();
+
+    let mut sites = YieldSites::new(&args);
+    if let Some(sites) = &mut sites {
+        sites.visit_block_mut(&mut body);
+    }
+
+    let info_const = info_const(
+        &args.krate(),
+        quote::quote! { #name },
+        Span::call_site(),
+        sites.as_ref(),
+    );
+    let gen = inline_generator(&args, &body, output.as_deref(), is_async);
+
+    Ok(quote::quote! {{
+        #( #warnings )*
+        #checks
+        #info_const
+        #gen
+    }})
+}
@@ -0,0 +1,163 @@
+use proc_macro2::TokenStream;
+use syn::ext::IdentExt;
+use syn::visit::{self, Visit};
+
+use crate::args::Args;
+use crate::generator::Warning;
+use crate::mac::ExprMacroArgs;
+
+/// Looks for uses of the generator macros that are guaranteed (or very likely)
+/// to panic at runtime so that they can be reported at compile time instead.
+///
+/// - `foreign_await`: an `.await` within a sync generator. Sync generators
+///   are polled with a waker that can't wake anything up, so they panic if
+///   any future other than the ones created by the generator macros returns
+///   pending. Futures that are immediately ready work fine, so this is only a
+///   warning.
+/// - `late_argument`: `argument!()` after the generator may have yielded. The
+///   initial argument is gone by then, so this always panics and is an error.
+///
+/// This works on the syntax of the body and so it can't be exact. It follows
+/// branches well enough to avoid false positives for the common cases but
+/// either check can be turned off with `allow(...)` in the generator arguments.
+///
+/// The body is expected to have already had its `yield` expressions expanded.
+pub(crate) struct CheckBody {
+    foreign_await: bool,
+    late_argument: bool,
+
+    /// Whether the generator may have yielded by the point being visited.
+    yielded: bool,
+    errors: Vec<syn::Error>,
+    warnings: Vec<Warning>,
+}
+
+impl CheckBody {
+    pub fn new(args: &Args, is_async: bool) -> Self {
+        Self {
+            foreign_await: !is_async && !args.allows("foreign_await"),
+            late_argument: !args.allows("late_argument"),
+            yielded: false,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// The errors and warnings that were found, to be emitted within the body.
+    pub fn into_diagnostics(self) -> TokenStream {
+        let errors = self.errors.into_iter().map(syn::Error::into_compile_error);
+        let warnings = self.warnings;
+
+        quote::quote! {
+            #( #errors )*
+            #( #warnings )*
+        }
+    }
+
+    /// Whether `block` yields on any path through it.
+    fn yields(block: &syn::Block) -> bool {
+        let mut check = Self {
+            foreign_await: false,
+            late_argument: false,
+            yielded: false,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+        check.visit_block(block);
+        check.yielded
+    }
+
+    /// Loop bodies may run again after they have yielded, so an `argument!()`
+    /// anywhere within them is too late.
+    fn visit_loop_body(&mut self, body: &syn::Block) {
+        self.yielded |= Self::yields(body);
+        self.visit_block(body);
+    }
+}
+
+impl<'ast> Visit<'ast> for CheckBody {
+    fn visit_expr_await(&mut self, i: &'ast syn::ExprAwait) {
+        visit::visit_expr_await(self, i);
+
+        if self.foreign_await {
+            self.warnings.push(Warning::new(
+                i.await_token.span,
+                "`.await` within a sync generator panics unless the future is immediately ready \
+                 (add `allow(foreign_await)` to the generator arguments if it always is)",
+            ));
+        }
+    }
+
+    fn visit_expr_if(&mut self, i: &'ast syn::ExprIf) {
+        self.visit_expr(&i.cond);
+
+        let before = self.yielded;
+        self.visit_block(&i.then_branch);
+        let then_yielded = std::mem::replace(&mut self.yielded, before);
+
+        if let Some((_, else_branch)) = &i.else_branch {
+            self.visit_expr(else_branch);
+        }
+        self.yielded |= then_yielded;
+    }
+
+    fn visit_expr_match(&mut self, i: &'ast syn::ExprMatch) {
+        self.visit_expr(&i.expr);
+
+        let before = self.yielded;
+        let mut after = before;
+        for arm in &i.arms {
+            self.yielded = before;
+            self.visit_arm(arm);
+            after |= self.yielded;
+        }
+        self.yielded = after;
+    }
+
+    fn visit_expr_loop(&mut self, i: &'ast syn::ExprLoop) {
+        self.visit_loop_body(&i.body);
+    }
+
+    fn visit_expr_while(&mut self, i: &'ast syn::ExprWhile) {
+        self.visit_expr(&i.cond);
+        self.visit_loop_body(&i.body);
+    }
+
+    fn visit_expr_for_loop(&mut self, i: &'ast syn::ExprForLoop) {
+        self.visit_expr(&i.expr);
+        self.visit_loop_body(&i.body);
+    }
+
+    fn visit_macro(&mut self, i: &'ast syn::Macro) {
+        if let Some(mut args) = ExprMacroArgs::parse(i) {
+            for expr in args.exprs_mut() {
+                self.visit_expr(expr);
+            }
+        }
+
+        let Some(name) = i.path.get_ident().map(IdentExt::unraw) else {
+            return;
+        };
+
+        if name == "yield"
+            || name == "yield_batch"
+            || name == "recurse"
+            || name == "yield_from_boxed"
+        {
+            self.yielded = true;
+        } else if name == "argument" && self.yielded && self.late_argument {
+            self.errors.push(syn::Error::new_spanned(
+                i,
+                "`argument!()` panics after the generator has yielded, use the value returned by \
+                 `r#yield!` instead (or add `allow(late_argument)` to the generator arguments)",
+            ));
+        }
+    }
+
+    // Closures, async blocks and nested items are evaluated separately from the
+    // generator body. Any `.await` on an async block is still caught at the point
+    // where it is awaited.
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
@@ -0,0 +1,121 @@
+use proc_macro2::{Span, TokenStream};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::Result;
+
+use crate::args::Args;
+use crate::check::CheckBody;
+use crate::generator::{check_try, info_const, inline_generator, ExpandYield};
+use crate::introspect::YieldSites;
+
+/// Input:
+/// ```ignore
+/// closure!(#[generator(yield = A, arg = B)] async? move? |x: u32| -> Ret { ... })
+/// ```
+///
+/// Output:
+/// ```ignore
+/// {
+///     const __INFO: GeneratorInfo = ...;
+///     move? |x: u32| gen_sync(TokenMarker::<A, B>::new(), &__INFO, async move { ... })
+/// }
+/// ```
+pub fn expand(input: TokenStream) -> Result<TokenStream> {
+    let mut closure = syn::parse::Parser::parse2(
+        |input: syn::parse::ParseStream| {
+            let attrs = input.call(syn::Attribute::parse_outer)?;
+            let mut closure: syn::ExprClosure = input.parse()?;
+            closure.attrs.splice(0..0, attrs);
+            Ok(closure)
+        },
+        input,
+    )?;
+
+    let index = closure
+        .attrs
+        .iter()
+        .position(|attr| {
+            attr.path()
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "generator")
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "expected a closure with a `#[generator(...)]` attribute",
+            )
+        })?;
+    let attr = closure.attrs.remove(index);
+    let args: Args = match &attr.meta {
+        syn::Meta::Path(_) => syn::parse2(TokenStream::new())?,
+        syn::Meta::List(list) => syn::parse2(list.tokens.clone())?,
+        syn::Meta::NameValue(meta) => {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "expected `#[generator(...)]`",
+            ))
+        }
+    };
+
+    if let Some(recursive) = &args.recursive {
+        return Err(syn::Error::new_spanned(
+            recursive,
+            format!("`{recursive}` generators cannot be closures"),
+        ));
+    }
+
+    if let Some(maybe_async) = &args.maybe_async {
+        return Err(syn::Error::new_spanned(
+            maybe_async,
+            format!("`{maybe_async}` generators cannot be closures"),
+        ));
+    }
+    check_try(&args)?;
+    args.check_send()?;
+
+    let yield_ident = syn::Ident::new_raw("yield", Span::call_site());
+
+    let mut warnings = Vec::new();
+    ExpandYield::new(yield_ident, &mut warnings).visit_expr_mut(&mut closure.body);
+
+    let mut check = CheckBody::new(&args, closure.asyncness.is_some());
+    check.visit_expr(&closure.body);
+    let checks = check.into_diagnostics();
+
+    let mut sites = YieldSites::new(&args);
+    if let Some(sites) = &mut sites {
+        sites.visit_expr_mut(&mut closure.body);
+    }
+
+    // The location is spanned to the start of the closure so that line!() and
+    // column!() point at it.
+    let info_const = info_const(
+        &args.krate(),
+        quote::quote! { ::core::concat!(::core::module_path!(), "::{closure}") },
+        closure.or1_token.span,
+        sites.as_ref(),
+    );
+
+    // The return type can't be written on the closure itself since it returns the
+    // generator instead.
+    let output = match std::mem::replace(&mut closure.output, syn::ReturnType::Default) {
+        syn::ReturnType::Type(_, ty) => Some(ty),
+        syn::ReturnType::Default => None,
+    };
+    let gen = inline_generator(
+        &args,
+        &closure.body,
+        output.as_deref(),
+        closure.asyncness.take().is_some(),
+    );
+
+    *closure.body = syn::parse_quote!({ #gen });
+
+    Ok(quote::quote! {{
+        #( #warnings )*
+        #checks
+        #info_const
+        #closure
+    }})
+}
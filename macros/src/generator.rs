@@ -3,17 +3,30 @@ use std::borrow::Cow;
 use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::spanned::Spanned;
+use syn::visit::Visit;
 use syn::visit_mut::{self, VisitMut};
 use syn::Result;
 
 use crate::args::Args;
+use crate::check::CheckBody;
+use crate::introspect::{sites_ident, YieldSites};
 use crate::lifetime::CollectLifetimes;
+use crate::mac::{contains_ident, ExprMacroArgs};
 
-struct Warning {
+pub(crate) struct Warning {
     span: Span,
     message: Cow<'static, str>,
 }
 
+impl Warning {
+    pub fn new(span: Span, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
 impl ToTokens for Warning {
     fn to_token_stream(&self) -> TokenStream {
         let message = syn::LitStr::new(&self.message, self.span);
@@ -41,26 +54,323 @@ impl ToTokens for Warning {
 }
 
 pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
-    let mut func: syn::ItemFn = syn::parse2(item)?;
+    let func: syn::ItemFn = syn::parse2(item)?;
     let args: Args = syn::parse2(attr)?;
+    args.check_send()?;
 
-    let krate = match &args.crate_ {
-        Some(krate) => krate.value.clone(),
-        None => syn::parse_quote!(::fauxgen),
-    };
-    let mut yield_ty = match &args.yield_ {
-        Some(ty) => ty.value.clone(),
-        None => syn::parse_quote!(()),
-    };
-    let mut arg_ty = match &args.arg {
-        Some(ty) => ty.value.clone(),
-        None => syn::parse_quote!(()),
+    match &args.maybe_async {
+        Some(maybe_async) => crate::maybe_async::expand(&args, maybe_async, func),
+        None => expand_fn(&args, func, true),
+    }
+}
+
+/// Expand a single generator function. If `diagnostics` is false then the
+/// warnings and errors about the body are left out, since they have already
+/// been emitted for another expansion of the same body.
+pub(crate) fn expand_fn(
+    args: &Args,
+    mut func: syn::ItemFn,
+    diagnostics: bool,
+) -> Result<TokenStream> {
+    let krate = args.krate();
+    let first_arg = take_arg_param(&mut func.sig)?;
+    let mut yield_ty = args.yield_ty();
+    let mut arg_ty = match (&args.arg, &first_arg) {
+        (None, Some(param)) => param.ty.clone(),
+        _ => args.arg_ty(),
     };
     let mut return_ty = match std::mem::replace(&mut func.sig.output, syn::ReturnType::Default) {
         syn::ReturnType::Default => syn::parse_quote!(()),
         syn::ReturnType::Type(_, ty) => ty,
     };
 
+    if let Some(recursive) = &args.recursive {
+        if let Some(asyncness) = &func.sig.asyncness {
+            return Err(syn::Error::new_spanned(
+                asyncness,
+                format!("`{recursive}` generators cannot be async"),
+            ));
+        }
+
+        if let Some(arg) = &args.arg {
+            return Err(syn::Error::new_spanned(
+                arg,
+                format!("`{recursive}` generators cannot take an argument"),
+            ));
+        }
+
+        if let Some(param) = &first_arg {
+            return Err(syn::Error::new_spanned(
+                &param.attrs[0],
+                format!("`{recursive}` generators cannot take an argument"),
+            ));
+        }
+
+        if let Some(size_hint) = &args.size_hint {
+            return Err(syn::Error::new_spanned(
+                size_hint,
+                format!("`{recursive}` generators cannot have a size hint"),
+            ));
+        }
+
+        if let Some(try_) = &args.try_ {
+            return Err(syn::Error::new_spanned(
+                try_,
+                format!("`{recursive}` generators cannot be `try` generators"),
+            ));
+        }
+
+        if let Some(max_size) = &args.max_size {
+            return Err(syn::Error::new_spanned(
+                max_size,
+                format!("`{recursive}` generators cannot have a maximum size"),
+            ));
+        }
+
+        if let Some(boxed_state) = &args.boxed_state {
+            return Err(syn::Error::new_spanned(
+                boxed_state,
+                format!("`{recursive}` generators cannot be `{boxed_state}`"),
+            ));
+        }
+
+        if let Some(introspect) = &args.introspect {
+            return Err(syn::Error::new_spanned(
+                introspect,
+                format!("`{recursive}` generators cannot be introspected"),
+            ));
+        }
+    }
+    check_try(args)?;
+    if let (Some(_), Some(param)) = (&args.try_, &first_arg) {
+        return Err(syn::Error::new_spanned(
+            &param.attrs[0],
+            "`try` generators cannot take an argument",
+        ));
+    }
+
+    let marker = syn::Ident::new("__marker", Span::mixed_site());
+    let info = info_ident();
+    let yield_ident = syn::Ident::new_raw("yield", Span::call_site());
+
+    let mut warnings = Vec::new();
+    expand_yield(&yield_ident, &mut warnings, &mut func.block);
+
+    let checks = match diagnostics {
+        true => {
+            let mut check = CheckBody::new(args, func.sig.asyncness.is_some());
+            check.visit_block(&func.block);
+            check.into_diagnostics()
+        }
+        false => {
+            warnings.clear();
+            TokenStream::new()
+        }
+    };
+
+    let mut sites = YieldSites::new(args);
+    if let Some(sites) = &mut sites {
+        sites.visit_block_mut(&mut func.block);
+    }
+
+    let gen_lt = transform_sig(&mut func.sig, &mut yield_ty, &mut arg_ty, &mut return_ty);
+
+    let block = func.block;
+
+    let token_init = match &args.recursive {
+        Some(_) => quote::quote! { #marker.token(&#info) },
+        None => quote::quote! { #krate::__private::token::<#yield_ty, #arg_ty>(&#info) },
+    };
+    let takes_arg = args.arg.is_some() || first_arg.is_some();
+    let mut prelude = prelude(args, &krate, token_init, takes_arg);
+    if let Some(param) = &first_arg {
+        let argument_ident = syn::Ident::new("argument", Span::call_site());
+        let pat = &param.pat;
+        let ty = &param.ty;
+        prelude.extend(quote::quote_spanned! { param.span() =>
+            let #pat: #ty = #argument_ident!();
+        });
+    }
+
+    // The location is spanned to the function name so that line!() and column!()
+    // point at the generator definition.
+    let name = &func.sig.ident;
+    let info_const = info_const(
+        &krate,
+        quote::quote! {
+            ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#name))
+        },
+        name.span(),
+        sites.as_ref(),
+    );
+
+    let gen_bound = match &gen_lt {
+        Some(lt) => quote::quote!(+ #lt),
+        None => TokenStream::new(),
+    };
+
+    let with_size_hint = |gen, func| with_size_hint(args, &krate, gen, func);
+    let with_try = |ty: syn::Type, wrapper| with_try(args, &krate, ty, wrapper);
+
+    if args.recursive.is_some() {
+        let lt = gen_lt.unwrap_or_else(|| syn::Lifetime::new("'static", Span::call_site()));
+
+        func.sig.output = syn::parse_quote!(
+            -> #krate::Recursive<#lt, #yield_ty, #return_ty>
+        );
+        func.block = syn::parse_quote!({
+            #info_const
+            let #marker = #krate::__private::TokenMarker::new();
+            #krate::__private::gen_recursive(
+                #marker,
+                &#info,
+                async move {
+                    #prelude
+                    #block
+                }
+            )
+        });
+    } else if func.sig.asyncness.take().is_some() {
+        let output = with_try(
+            syn::parse_quote!(
+                #krate::__private::AsyncGenerator<
+                    impl #krate::__private::Future<Output = #return_ty> #gen_bound,
+                    #yield_ty,
+                    #arg_ty,
+                >
+            ),
+            "GeneratorTryStream",
+        );
+        func.sig.output = syn::parse_quote!(-> #output);
+        let future = with_boxed_state(
+            args,
+            &krate,
+            quote::quote! {
+                async move {
+                    #prelude
+                    #block
+                }
+            },
+        );
+        let gen = quote::quote! {
+            #krate::__private::gen_async(
+                #krate::__private::TokenMarker::new(),
+                &#info,
+                #future
+            )
+        };
+        let gen = with_max_size(args, gen);
+        let gen = with_size_hint(gen, "async_with_size_hint");
+        let gen = try_wrapper(args, &krate, gen, "try_stream");
+
+        func.block = syn::parse_quote!({
+            #info_const
+            #gen
+        });
+    } else {
+        let output = with_try(
+            syn::parse_quote!(
+                #krate::__private::SyncGenerator<
+                    impl #krate::__private::Future<Output = #return_ty> #gen_bound,
+                    #yield_ty,
+                    #arg_ty,
+                >
+            ),
+            "GeneratorTryIter",
+        );
+        func.sig.output = syn::parse_quote!(-> #output);
+        let future = with_boxed_state(
+            args,
+            &krate,
+            quote::quote! {
+                async move {
+                    #prelude
+                    #block
+                }
+            },
+        );
+        let gen = quote::quote! {
+            #krate::__private::gen_sync(
+                #krate::__private::TokenMarker::new(),
+                &#info,
+                #future
+            )
+        };
+        let gen = with_max_size(args, gen);
+        let gen = with_size_hint(gen, "sync_with_size_hint");
+        let gen = try_wrapper(args, &krate, gen, "try_iter");
+
+        func.block = syn::parse_quote!({
+            #info_const
+            #gen
+        });
+    }
+
+    Ok(quote::quote! {
+        #( #warnings )*
+        #checks
+
+        #func
+    })
+}
+
+/// The ident of the constant holding the `GeneratorInfo` for a generator.
+pub(crate) fn info_ident() -> syn::Ident {
+    syn::Ident::new("__INFO", Span::mixed_site())
+}
+
+/// Declare the constant holding the information used in panic messages and
+/// `Debug` output. `span` should point at the generator definition so that
+/// `line!()` and `column!()` do too.
+///
+/// If the generator has `sites` then their table is declared as well. The
+/// information is then a static, since it refers to the table.
+pub(crate) fn info_const(
+    krate: &syn::Path,
+    name: TokenStream,
+    span: Span,
+    sites: Option<&YieldSites>,
+) -> TokenStream {
+    let info = info_ident();
+    let (table, item, sites) = match sites {
+        Some(sites) => {
+            let ident = sites_ident();
+            (
+                sites.table(krate),
+                quote::quote!(static),
+                quote::quote!(&#ident),
+            )
+        }
+        None => (TokenStream::new(), quote::quote!(const), quote::quote!(&[])),
+    };
+
+    let info = quote::quote_spanned! { span =>
+        #item #info: #krate::__private::GeneratorInfo = #krate::__private::GeneratorInfo {
+            name: #name,
+            file: ::core::file!(),
+            line: ::core::line!(),
+            column: ::core::column!(),
+            sites: #sites,
+        };
+    };
+
+    quote::quote! {
+        #table
+        #info
+    }
+}
+
+/// The statements at the start of the generator body that register the token
+/// and declare the macros used within the body.
+///
+/// `takes_arg` is whether the generator has an argument type other than the
+/// default `()`, either from `arg` or from a `#[arg]` parameter.
+pub(crate) fn prelude(
+    args: &Args,
+    krate: &syn::Path,
+    token_init: TokenStream,
+    takes_arg: bool,
+) -> TokenStream {
     // By using mixed-site hygiene we ensure that user code within the function can
     // never actually use this token.
     //
@@ -69,26 +379,43 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
     let token = syn::Ident::new("__token", Span::mixed_site());
     let yield_ident = syn::Ident::new_raw("yield", Span::call_site());
     let argument_ident = syn::Ident::new("argument", Span::call_site());
-
-    let mut warnings = Vec::new();
-    expand_yield(&token, &mut warnings, &mut func.block);
-    transform_sig(
-        &mut func.sig,
-        &mut yield_ty,
-        &mut arg_ty,
-        &mut return_ty,
-        &krate,
-    );
-
-    let block = func.block;
+    let recurse_ident = syn::Ident::new("recurse", Span::call_site());
+    let yield_from_boxed_ident = syn::Ident::new("yield_from_boxed", Span::call_site());
+    let size_hint_ident = syn::Ident::new("size_hint", Span::call_site());
+    let yield_batch_ident = syn::Ident::new("yield_batch", Span::call_site());
 
     let yield_macro_span = args
         .yield_
         .as_ref()
         .map(|arg| arg.span())
         .unwrap_or(Span::call_site());
-    let yield_macro_body = quote::quote_spanned! {
-        yield_macro_span => #token.yield_($value).await
+    let yield_macro_body = match &args.recursive {
+        Some(_) => quote::quote_spanned! { yield_macro_span =>
+            { #token.yield_(#krate::__private::RecursiveStep::Yield($value)).await; }
+        },
+        None => quote::quote_spanned! { yield_macro_span =>
+            #token.yield_($value).await
+        },
+    };
+
+    // Sites numbered by `YieldSites` are passed on to the token.
+    let (yield_site_arm, batch_site_arm) = match &args.introspect {
+        Some(_) => {
+            let sites = sites_ident();
+            (
+                quote::quote_spanned! { yield_macro_span =>
+                    (@site $site:literal, $value:expr) => {
+                        #token.yield_($value).at(&#sites[$site]).await
+                    };
+                },
+                quote::quote! {
+                    (@site $site:literal, $iter:expr) => {
+                        #token.yield_batch($iter).at(&#sites[$site]).await
+                    };
+                },
+            )
+        }
+        None => (TokenStream::new(), TokenStream::new()),
     };
 
     let argument_macro_span = args
@@ -100,8 +427,69 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
         argument_macro_span => #token.argument().await
     };
 
-    let prelude = quote::quote! {
-        let #token = #krate::__private::token::<#yield_ty, #arg_ty>();
+    // Recursive generators are made up of many generators so setting the size hint
+    // or batching the yields of just one of them wouldn't be meaningful.
+    let size_hint_macro = match &args.recursive {
+        Some(_) => TokenStream::new(),
+        None => quote::quote! {
+            /// Update the bounds on the number of values this generator has left to yield.
+            #[allow(unused_macros)]
+            macro_rules! #size_hint_ident {
+                ($lower:expr, $upper:expr $(,)?) => {
+                    #token.set_size_hint($lower, $upper).await
+                };
+            }
+        },
+    };
+
+    // Each value in a batch is yielded without taking an argument, so batches are
+    // only available to generators that don't take one.
+    let yield_batch_macro = match (&args.recursive, takes_arg) {
+        (Some(_), _) | (_, true) => TokenStream::new(),
+        (None, false) => quote::quote! {
+            /// Yield every value from an iterator while only suspending the generator once.
+            #[allow(unused_macros)]
+            macro_rules! #yield_batch_ident {
+                #batch_site_arm
+                ($iter:expr) => {
+                    #token.yield_batch($iter).await
+                };
+            }
+        },
+    };
+
+    let recurse_macro = match &args.recursive {
+        Some(recursive) => {
+            let recurse_macro_span = recursive.span();
+            let recurse_macro_body = quote::quote_spanned! {
+                recurse_macro_span => #krate::__private::recurse(#token, $gen).await
+            };
+            let yield_from_boxed_macro_body = quote::quote_spanned! {
+                recurse_macro_span => #krate::__private::recurse(
+                    #token,
+                    #krate::Recursive::boxed($gen),
+                ).await
+            };
+
+            quote::quote! {
+                /// Run a recursive generator to completion, yielding all of its values.
+                #[allow(unused_macros)]
+                macro_rules! #recurse_ident {
+                    ($gen:expr) => { #recurse_macro_body }
+                }
+
+                /// Run any generator to completion, yielding all of its values.
+                #[allow(unused_macros)]
+                macro_rules! #yield_from_boxed_ident {
+                    ($gen:expr) => { #yield_from_boxed_macro_body }
+                }
+            }
+        }
+        None => TokenStream::new(),
+    };
+
+    quote::quote! {
+        let #token = #token_init;
         let #token = #krate::__private::pin!(#token);
         let #token = #token.as_ref();
         #krate::__private::register(#token).await;
@@ -110,6 +498,7 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
         /// Yield a value from this generator.
         #[allow(unused_macros)]
         macro_rules! #yield_ident {
+            #yield_site_arm
             ()            => { #yield_ident!(()) };
             ($value:expr) => { #yield_macro_body }
         }
@@ -119,50 +508,228 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
         macro_rules! #argument_ident {
             () => { #argument_macro_body }
         }
+
+        #size_hint_macro
+        #yield_batch_macro
+        #recurse_macro
+    }
+}
+
+/// Wrap the expression creating a generator so that it starts out with the
+/// size hint given in `args`, if any. `func` is the `__private` function that
+/// sets the hint for this kind of generator.
+///
+/// The initial size hint is evaluated before the arguments are moved into the
+/// generator.
+pub(crate) fn with_size_hint(
+    args: &Args,
+    krate: &syn::Path,
+    gen: TokenStream,
+    func: &str,
+) -> TokenStream {
+    let size_hint = syn::Ident::new("__size_hint", Span::mixed_site());
+
+    match &args.size_hint {
+        Some(hint) => {
+            let expr = &hint.value;
+            let func = syn::Ident::new(func, Span::call_site());
+
+            quote::quote! {{
+                let #size_hint: (usize, ::core::option::Option<usize>) = #expr;
+                #krate::__private::#func(#gen, #size_hint)
+            }}
+        }
+        None => gen,
+    }
+}
+
+/// Move the future of a generator to the heap if it has `boxed_state`.
+///
+/// With a `max_size` as well, the future is only moved to the heap if it
+/// doesn't fit within the generator. The size of the future isn't known until
+/// the generator is instantiated, so this is decided by `BoundedState` then.
+pub(crate) fn with_boxed_state(args: &Args, krate: &syn::Path, future: TokenStream) -> TokenStream {
+    match (&args.boxed_state, &args.max_size) {
+        (Some(_), Some(max_size)) => {
+            let value = &max_size.value;
+            quote::quote! {
+                #krate::__private::boxed_within::<
+                    { #krate::__private::inline_budget(#value) },
+                    _,
+                >(#future)
+            }
+        }
+        (Some(_), None) => quote::quote! { #krate::__private::boxed(#future) },
+        (None, _) => future,
+    }
+}
+
+/// Wrap the expression creating a generator in a check that the generator is
+/// no larger than its `max_size`, if it has one.
+///
+/// The type of the generator can't be named so the check happens within a
+/// generic function, which means it fails when the generator is instantiated
+/// rather than when it is type checked. It is spanned to the `max_size`
+/// argument so that the error points there.
+///
+/// Generators with `boxed_state` are left unchecked, since their state is
+/// moved to the heap whenever it would not fit.
+pub(crate) fn with_max_size(args: &Args, gen: TokenStream) -> TokenStream {
+    let Some(max_size) = &args.max_size else {
+        return gen;
     };
+    if args.boxed_state.is_some() {
+        return gen;
+    }
 
-    if func.sig.asyncness.take().is_some() {
-        func.block = syn::parse_quote!({
-            #krate::__private::gen_async(
-                #krate::__private::TokenMarker::new(),
-                async move {
-                    #prelude
-                    #block
-                }
-            )
-        });
-    } else {
-        func.block = syn::parse_quote!({
-            #krate::__private::gen_sync(
-                #krate::__private::TokenMarker::new(),
-                async move {
-                    #prelude
-                    #block
-                }
-            )
-        });
+    let check = syn::Ident::new("__check_size", Span::mixed_site());
+    let value = &max_size.value;
+
+    quote::quote_spanned! { max_size.span() => {
+        fn #check<G>(gen: G) -> G {
+            const {
+                ::core::assert!(
+                    ::core::mem::size_of::<G>() <= #value,
+                    "the generator is larger than its `max_size`, \
+                     use `boxed_state` to store its state on the heap instead",
+                )
+            };
+            gen
+        }
+
+        #check(#gen)
+    }}
+}
+
+/// Check that the arguments of a `try` generator are consistent.
+pub(crate) fn check_try(args: &Args) -> Result<()> {
+    match (&args.try_, &args.arg) {
+        (Some(_), Some(arg)) => Err(syn::Error::new_spanned(
+            arg,
+            "`try` generators cannot take an argument",
+        )),
+        _ => Ok(()),
     }
+}
 
-    Ok(quote::quote! {
-        #( #warnings )*
+/// The type returned by a generator, wrapped in `wrapper` if it is a `try`
+/// generator.
+fn with_try(args: &Args, krate: &syn::Path, ty: syn::Type, wrapper: &str) -> syn::Type {
+    match &args.try_ {
+        Some(_) => {
+            let wrapper = syn::Ident::new(wrapper, Span::call_site());
+            syn::parse_quote!(#krate::#wrapper<#ty>)
+        }
+        None => ty,
+    }
+}
 
-        #func
-    })
+/// Wrap the expression creating a `try` generator so that it yields results.
+/// `func` is the `__private` function that wraps this kind of generator.
+pub(crate) fn try_wrapper(
+    args: &Args,
+    krate: &syn::Path,
+    gen: TokenStream,
+    func: &str,
+) -> TokenStream {
+    match &args.try_ {
+        Some(_) => {
+            let func = syn::Ident::new(func, Span::call_site());
+            quote::quote! { #krate::__private::#func(#gen) }
+        }
+        None => gen,
+    }
+}
+
+/// Build the future that runs the body of a generator which is defined
+/// inline, with the prelude of the generator placed before `body`.
+pub(crate) fn inline_future(args: &Args, body: &dyn ToTokens) -> TokenStream {
+    let krate = args.krate();
+    let yield_ty = args.yield_ty();
+    let arg_ty = args.arg_ty();
+    let info = info_ident();
+
+    let token_init = quote::quote! { #krate::__private::token::<#yield_ty, #arg_ty>(&#info) };
+    let prelude = prelude(args, &krate, token_init, args.arg.is_some());
+
+    quote::quote! {
+        async move {
+            #prelude
+            #body
+        }
+    }
 }
 
-struct ExpandYield<'w> {
-    token: syn::Ident,
+/// Build the expression creating a generator that is defined inline, by
+/// `closure!` or `gen_block!`.
+///
+/// The `yield` expressions in `body` must already have been expanded and the
+/// constant declared by [`info_const`] must be in scope. If `output` is given
+/// then the generator is constrained to return it.
+pub(crate) fn inline_generator(
+    args: &Args,
+    body: &dyn ToTokens,
+    output: Option<&syn::Type>,
+    is_async: bool,
+) -> TokenStream {
+    let krate = args.krate();
+    let yield_ty = args.yield_ty();
+    let arg_ty = args.arg_ty();
+    let info = info_ident();
+
+    let future = inline_future(args, body);
+    let future = match output {
+        Some(ty) => quote::quote! { #krate::__private::returns::<#ty, _>(#future) },
+        None => future,
+    };
+    let future = with_boxed_state(args, &krate, future);
+
+    let (genfn, with_hint, try_func) = match is_async {
+        true => ("gen_async", "async_with_size_hint", "try_stream"),
+        false => ("gen_sync", "sync_with_size_hint", "try_iter"),
+    };
+    let genfn = syn::Ident::new(genfn, Span::call_site());
+    let gen = quote::quote! {
+        #krate::__private::#genfn(
+            #krate::__private::TokenMarker::<#yield_ty, #arg_ty>::new(),
+            &#info,
+            #future
+        )
+    };
+
+    let gen = with_max_size(args, gen);
+    let gen = with_size_hint(args, &krate, gen, with_hint);
+    try_wrapper(args, &krate, gen, try_func)
+}
+
+pub(crate) struct ExpandYield<'w> {
+    yield_macro: syn::Ident,
     warnings: &'w mut Vec<Warning>,
 }
 
 impl<'w> ExpandYield<'w> {
-    fn new(name: syn::Ident, warnings: &'w mut Vec<Warning>) -> Self {
+    pub fn new(yield_macro: syn::Ident, warnings: &'w mut Vec<Warning>) -> Self {
         Self {
-            token: name,
+            yield_macro,
             warnings,
         }
     }
 
+    /// Expand the `yield` expressions within the arguments of a macro.
+    ///
+    /// This only works for macros whose arguments are made up of expressions
+    /// (see [`ExprMacroArgs`]). A `yield` within any other macro is left as is
+    /// and gets a warning instead.
+    fn visit_macro_args(&mut self, mac: &mut syn::Macro) {
+        if !contains_ident(mac.tokens.clone(), "yield") {
+            return;
+        }
+
+        if !ExprMacroArgs::visit_mut(mac, self) {
+            self.visit_token_stream(mac.tokens.clone());
+        }
+    }
+
     fn visit_token_stream(&mut self, stream: TokenStream) {
         for tree in stream.into_iter() {
             self.visit_token_tree(&tree);
@@ -191,7 +758,10 @@ impl VisitMut for ExpandYield<'_> {
     fn visit_expr_mut(&mut self, i: &mut syn::Expr) {
         match i {
             syn::Expr::Yield(y) => {
-                let token = &self.token;
+                // The macro is spanned to the `yield` so that it can be used as
+                // the location of the yield site.
+                let mut yield_macro = self.yield_macro.clone();
+                yield_macro.set_span(y.yield_token.span);
                 let attrs = &y.attrs;
                 let expr = match &y.expr {
                     Some(expr) => expr.clone(),
@@ -199,7 +769,7 @@ impl VisitMut for ExpandYield<'_> {
                 };
                 *i = syn::parse_quote_spanned!( y.yield_token.span =>
                     #( #attrs )*
-                    #token.yield_(#expr).await
+                    #yield_macro!(#expr)
                 );
             }
             // Don't recurse into closures. They are a different function and may actually be a rust
@@ -210,14 +780,72 @@ impl VisitMut for ExpandYield<'_> {
     }
 
     fn visit_expr_macro_mut(&mut self, i: &mut syn::ExprMacro) {
-        self.visit_token_stream(i.mac.tokens.clone())
+        self.visit_macro_args(&mut i.mac)
     }
 
     fn visit_stmt_macro_mut(&mut self, i: &mut syn::StmtMacro) {
-        self.visit_token_stream(i.mac.tokens.clone())
+        self.visit_macro_args(&mut i.mac)
     }
 }
 
+/// Remove the parameter marked with `#[arg]` from `sig`, if there is one.
+///
+/// The parameter is returned with only the `#[arg]` attribute left.
+fn take_arg_param(sig: &mut syn::Signature) -> Result<Option<syn::PatType>> {
+    let mut first_arg: Option<syn::PatType> = None;
+    let mut inputs = syn::punctuated::Punctuated::new();
+
+    for input in std::mem::take(&mut sig.inputs) {
+        let mut param = match input {
+            syn::FnArg::Typed(param) => param,
+            receiver => {
+                inputs.push(receiver);
+                continue;
+            }
+        };
+
+        let Some(index) = param
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident("arg"))
+        else {
+            inputs.push(syn::FnArg::Typed(param));
+            continue;
+        };
+        let attr = param.attrs.remove(index);
+        attr.meta.require_path_only()?;
+
+        if first_arg.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only one parameter can be marked with `#[arg]`",
+            ));
+        }
+
+        param.attrs = vec![attr];
+        first_arg = Some(param);
+    }
+
+    sig.inputs = inputs;
+    Ok(first_arg)
+}
+
+/// Remove the `#[arg]` attributes from the parameters of `item` so that they
+/// don't cause more errors when it is emitted as is.
+pub fn strip_arg_attrs(item: TokenStream) -> TokenStream {
+    let Ok(mut func) = syn::parse2::<syn::ItemFn>(item.clone()) else {
+        return item;
+    };
+
+    for input in &mut func.sig.inputs {
+        if let syn::FnArg::Typed(param) = input {
+            param.attrs.retain(|attr| !attr.path().is_ident("arg"));
+        }
+    }
+
+    func.into_token_stream()
+}
+
 /// Input:
 /// ```ignore
 /// #[generator(yield = A, arg = B)]
@@ -226,21 +854,22 @@ impl VisitMut for ExpandYield<'_> {
 ///
 /// Output:
 /// ```ignore
-/// fn some_fn<'a, 'life2, 'r#gen>(self, x: &'a T, y: &'life2 u32) ->
-///     (Sync|Async)Generator<impl Future<Output = Ret> + 'r#gen, A, B>
+/// fn some_fn<'a, 'life2, 'r#gen>(self, x: &'a T, y: &'life2 u32)
 /// where
 ///     'a: 'r#gen,
 ///     'life2: 'r#gen,
 ///     T: 'r#gen,
 ///     Self: 'r#gen;
 /// ```
+///
+/// The return type is left for the caller to fill in. If the generator needs
+/// to be bounded by `'r#gen` then the lifetime is returned.
 fn transform_sig(
     sig: &mut syn::Signature,
     yield_ty: &mut syn::Type,
     arg_ty: &mut syn::Type,
     return_ty: &mut syn::Type,
-    krate: &syn::Path,
-) {
+) -> Option<syn::Lifetime> {
     use std::mem;
 
     let gen_lt: syn::Lifetime = syn::parse_quote_spanned! {
@@ -305,42 +934,24 @@ fn transform_sig(
         needs_gen = true;
     }
 
-    let gen_bound = if needs_gen {
-        if has_receiver {
-            where_clause_or_default(&mut sig.generics.where_clause)
-                .predicates
-                .push(syn::parse_quote!( Self: #gen_lt ));
-        }
+    if !needs_gen {
+        return None;
+    }
 
-        sig.generics.params.push(syn::parse_quote!(#gen_lt));
+    if has_receiver {
+        where_clause_or_default(&mut sig.generics.where_clause)
+            .predicates
+            .push(syn::parse_quote!( Self: #gen_lt ));
+    }
 
-        quote::quote!(+ #gen_lt)
-    } else {
-        TokenStream::new()
-    };
+    sig.generics.params.push(syn::parse_quote!(#gen_lt));
 
-    if sig.asyncness.is_none() {
-        sig.output = syn::parse_quote!(
-            -> #krate::__private::SyncGenerator<
-                impl #krate::__private::Future<Output = #return_ty> #gen_bound,
-                #yield_ty,
-                #arg_ty,
-            >
-        );
-    } else {
-        sig.output = syn::parse_quote!(
-            -> #krate::__private::AsyncGenerator<
-                impl #krate::__private::Future<Output = #return_ty> #gen_bound,
-                #yield_ty,
-                #arg_ty,
-            >
-        );
-    }
+    Some(gen_lt)
 }
 
 /// Replaces all instances of `yield $expr` in a block with `r#yield!($expr)`.
-fn expand_yield(macro_token: &syn::Ident, warnings: &mut Vec<Warning>, block: &mut syn::Block) {
-    ExpandYield::new(macro_token.clone(), warnings).visit_block_mut(block);
+fn expand_yield(yield_macro: &syn::Ident, warnings: &mut Vec<Warning>, block: &mut syn::Block) {
+    ExpandYield::new(yield_macro.clone(), warnings).visit_block_mut(block);
 }
 
 fn where_clause_or_default(clause: &mut Option<syn::WhereClause>) -> &mut syn::WhereClause {
@@ -0,0 +1,268 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::Result;
+
+use crate::args::Args;
+use crate::check::CheckBody;
+use crate::generator::{info_const, info_ident, inline_future, with_size_hint, ExpandYield};
+use crate::introspect::YieldSites;
+
+/// The attributes that implement a method of an impl block with a generator.
+#[derive(Clone, Copy)]
+pub enum Kind {
+    /// `#[into_iter]`, which returns a `BoxedIter`.
+    Iter,
+
+    /// `#[into_stream]`, which returns a `BoxedStream`.
+    Stream,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Iter => "into_iter",
+            Self::Stream => "into_stream",
+        }
+    }
+
+    /// The associated type of a trait impl that names the returned generator.
+    fn assoc_type(self) -> syn::Ident {
+        let name = match self {
+            Self::Iter => "IntoIter",
+            Self::Stream => "IntoStream",
+        };
+        syn::Ident::new(name, Span::call_site())
+    }
+
+    /// The type returned by the method, which is [`Send`] if `send` is given.
+    fn boxed_type(self, send: bool) -> syn::Ident {
+        let name = match (self, send) {
+            (Self::Iter, false) => "BoxedIter",
+            (Self::Iter, true) => "BoxedSendIter",
+            (Self::Stream, false) => "BoxedStream",
+            (Self::Stream, true) => "BoxedSendStream",
+        };
+        syn::Ident::new(name, Span::call_site())
+    }
+}
+
+/// Input:
+/// ```ignore
+/// #[into_iter(yield = &'a Node)]
+/// impl<'a> IntoIterator for &'a Tree {
+///     fn into_iter(self) { ... }
+/// }
+/// ```
+///
+/// Output:
+/// ```ignore
+/// impl<'a> IntoIterator for &'a Tree {
+///     type Item = &'a Node;
+///     type IntoIter = BoxedIter<'a, &'a Node>;
+///
+///     fn into_iter(self) -> Self::IntoIter {
+///         const __INFO: GeneratorInfo = ...;
+///         gen_sync(TokenMarker::<&'a Node, ()>::new(), &__INFO, boxed_dyn(async move { ... }))
+///     }
+/// }
+/// ```
+///
+/// Within an inherent impl only the method is changed, and it returns
+/// `BoxedIter<'_, A>` instead if it borrows `self`.
+pub fn expand(attr: TokenStream, item: TokenStream, kind: Kind) -> Result<TokenStream> {
+    let args: Args = syn::parse2(attr)?;
+    let mut item: syn::ItemImpl = syn::parse2(item)?;
+    let name = kind.name();
+
+    check_args(&args, name)?;
+
+    let krate = args.krate();
+    let yield_ty = args.yield_ty();
+    let assoc_type = kind.assoc_type();
+    let boxed_type = kind.boxed_type(args.send.is_some());
+
+    let method = find_method(&mut item, name)?;
+    let is_async = match kind {
+        Kind::Iter => {
+            if let Some(asyncness) = &method.sig.asyncness {
+                return Err(syn::Error::new_spanned(
+                    asyncness,
+                    "`#[into_iter]` methods cannot be async, use `#[into_stream]` instead",
+                ));
+            }
+            false
+        }
+        // The method returns the stream so it is never async itself, but being
+        // able to write `async fn` makes it clearer that the body can await.
+        Kind::Stream => {
+            method.sig.asyncness = None;
+            true
+        }
+    };
+
+    if let syn::ReturnType::Type(..) = &method.sig.output {
+        return Err(syn::Error::new_spanned(
+            &method.sig.output,
+            format!("`#[{name}]` methods cannot return a value"),
+        ));
+    }
+
+    let borrows_self = method
+        .sig
+        .receiver()
+        .is_some_and(|receiver| receiver.reference.is_some());
+
+    // The boxed future has to be bounded by a lifetime that can be named within
+    // the associated type, which is the lifetime of the impl if it has one. With
+    // several there's no telling which one the generator should borrow for, so
+    // they are rejected. Methods of inherent impls that borrow `self` can use the
+    // elided lifetime.
+    let lt = match (&item.trait_, borrows_self) {
+        (None, true) => syn::Lifetime::new("'_", Span::call_site()),
+        _ => {
+            let mut lifetimes = item.generics.lifetimes();
+
+            match (lifetimes.next(), lifetimes.next()) {
+                (Some(param), None) => param.lifetime.clone(),
+                (None, _) => syn::Lifetime::new("'static", Span::call_site()),
+                (Some(_), Some(second)) => {
+                    return Err(syn::Error::new_spanned(
+                        &second.lifetime,
+                        format!("`#[{name}]` impls cannot have more than one lifetime parameter"),
+                    ))
+                }
+            }
+        }
+    };
+
+    let output: syn::Type = match &item.trait_ {
+        Some(_) => {
+            let has_type = |ident: &syn::Ident| {
+                item.items.iter().any(|item| match item {
+                    syn::ImplItem::Type(ty) => ty.ident == *ident,
+                    _ => false,
+                })
+            };
+            let item_ident = syn::Ident::new("Item", Span::call_site());
+
+            let mut types: Vec<syn::ImplItem> = Vec::new();
+            if !has_type(&item_ident) {
+                types.push(syn::parse_quote!(type #item_ident = #yield_ty;));
+            }
+            if !has_type(&assoc_type) {
+                types.push(syn::parse_quote!(
+                    type #assoc_type = #krate::#boxed_type<#lt, #yield_ty>;
+                ));
+            }
+            item.items.splice(0..0, types);
+
+            syn::parse_quote!(Self::#assoc_type)
+        }
+        None => syn::parse_quote!(#krate::#boxed_type<#lt, #yield_ty>),
+    };
+
+    let self_ty = item.self_ty.clone();
+    let method = find_method(&mut item, name)?;
+    method.sig.output = syn::parse_quote!(-> #output);
+
+    let yield_ident = syn::Ident::new_raw("yield", Span::call_site());
+
+    let mut warnings = Vec::new();
+    ExpandYield::new(yield_ident, &mut warnings).visit_block_mut(&mut method.block);
+
+    let mut check = CheckBody::new(&args, is_async);
+    check.visit_block(&method.block);
+    let checks = check.into_diagnostics();
+
+    let mut sites = YieldSites::new(&args);
+    if let Some(sites) = &mut sites {
+        sites.visit_block_mut(&mut method.block);
+    }
+
+    let method_name = &method.sig.ident;
+    let info = info_ident();
+    let info_const = info_const(
+        &krate,
+        quote::quote! {
+            ::core::concat!(
+                ::core::module_path!(),
+                "::<",
+                ::core::stringify!(#self_ty),
+                ">::",
+                ::core::stringify!(#method_name),
+            )
+        },
+        method_name.span(),
+        sites.as_ref(),
+    );
+
+    let (genfn, with_hint) = match kind {
+        Kind::Iter => ("gen_sync", "sync_with_size_hint"),
+        Kind::Stream => ("gen_async", "async_with_size_hint"),
+    };
+    let genfn = syn::Ident::new(genfn, Span::call_site());
+    let boxed = match &args.send {
+        Some(_) => syn::Ident::new("boxed_dyn_send", Span::call_site()),
+        None => syn::Ident::new("boxed_dyn", Span::call_site()),
+    };
+    let future = inline_future(&args, &method.block);
+    let gen = quote::quote! {
+        #krate::__private::#genfn(
+            #krate::__private::TokenMarker::<#yield_ty, ()>::new(),
+            &#info,
+            #krate::__private::#boxed(#future)
+        )
+    };
+    let gen = with_size_hint(&args, &krate, gen, with_hint);
+
+    method.block = syn::parse_quote!({
+        #( #warnings )*
+        #checks
+        #info_const
+        #gen
+    });
+
+    Ok(item.into_token_stream())
+}
+
+/// The one method within the impl block.
+fn find_method<'a>(item: &'a mut syn::ItemImpl, name: &str) -> Result<&'a mut syn::ImplItemFn> {
+    let span = item.impl_token.span;
+    let mut methods = item.items.iter_mut().filter_map(|item| match item {
+        syn::ImplItem::Fn(method) => Some(method),
+        _ => None,
+    });
+
+    match (methods.next(), methods.next()) {
+        (Some(method), None) => Ok(method),
+        _ => Err(syn::Error::new(
+            span,
+            format!("`#[{name}]` impl blocks must contain exactly one method"),
+        )),
+    }
+}
+
+/// Reject the generator arguments that don't apply to these generators.
+fn check_args(args: &Args, name: &str) -> Result<()> {
+    let unsupported: [Option<&dyn ToTokens>; 6] = [
+        args.arg.as_ref().map(|arg| &arg.key as _),
+        args.recursive.as_ref().map(|arg| arg as _),
+        args.try_.as_ref().map(|arg| arg as _),
+        args.maybe_async.as_ref().map(|arg| arg as _),
+        args.max_size.as_ref().map(|arg| &arg.key as _),
+        args.boxed_state.as_ref().map(|arg| arg as _),
+    ];
+
+    match unsupported.into_iter().flatten().next() {
+        Some(arg) => Err(syn::Error::new_spanned(
+            arg,
+            format!(
+                "`{}` is not supported by `#[{name}]`",
+                arg.to_token_stream()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
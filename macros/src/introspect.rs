@@ -0,0 +1,120 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::ext::IdentExt;
+use syn::visit_mut::VisitMut;
+
+use crate::args::Args;
+use crate::mac::ExprMacroArgs;
+
+/// The ident of the static table holding the yield sites of a generator.
+pub(crate) fn sites_ident() -> syn::Ident {
+    syn::Ident::new("__SITES", Span::mixed_site())
+}
+
+/// Numbers the yield sites within the body of an `introspect` generator.
+///
+/// Each `r#yield!(value)` becomes `r#yield!(@site N, value)`, and the same for
+/// `yield_batch!`, where `N` is the index of the site within the table emitted
+/// by [`table`](Self::table). The macros declared in the prelude pass the site
+/// on to the token when they are given one.
+///
+/// The body is expected to have already had its `yield` expressions expanded
+/// and to have been checked by `CheckBody`, which doesn't understand the
+/// numbered form.
+pub(crate) struct YieldSites {
+    spans: Vec<Span>,
+    counters: bool,
+}
+
+impl YieldSites {
+    /// Returns `None` unless the generator has `introspect`.
+    pub fn new(args: &Args) -> Option<Self> {
+        let introspect = args.introspect.as_ref()?;
+
+        Some(Self {
+            spans: Vec::new(),
+            counters: introspect.counters(),
+        })
+    }
+
+    /// Declare the static table of the sites that have been numbered.
+    ///
+    /// Each entry is spanned to its site so that `line!()` and `column!()`
+    /// point at it.
+    pub fn table(&self, krate: &syn::Path) -> TokenStream {
+        let sites = sites_ident();
+        let len = self.spans.len();
+        let counters = self.counters;
+        let entries = self.spans.iter().enumerate().map(|(index, &span)| {
+            quote::quote_spanned! { span =>
+                #krate::__private::YieldSite::new(
+                    #index,
+                    ::core::file!(),
+                    ::core::line!(),
+                    ::core::column!(),
+                    #counters,
+                )
+            }
+        });
+
+        quote::quote! {
+            static #sites: [#krate::__private::YieldSite; #len] = [ #( #entries ),* ];
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &mut syn::Macro) {
+        if !contains_site(mac.tokens.clone()) && !is_site(mac) {
+            return;
+        }
+
+        // Sites are numbered in the order they appear, so an outer yield comes
+        // before any that are nested within its value.
+        let index = is_site(mac).then(|| {
+            self.spans.push(mac.path.segments[0].ident.span());
+            self.spans.len() - 1
+        });
+
+        ExprMacroArgs::visit_mut(mac, self);
+
+        if let Some(index) = index {
+            let tokens = match mac.tokens.is_empty() {
+                true => quote::quote!(()),
+                false => std::mem::take(&mut mac.tokens),
+            };
+
+            mac.tokens = quote::quote!(@site #index, #tokens);
+        }
+    }
+}
+
+impl VisitMut for YieldSites {
+    fn visit_expr_macro_mut(&mut self, i: &mut syn::ExprMacro) {
+        self.visit_macro(&mut i.mac)
+    }
+
+    fn visit_stmt_macro_mut(&mut self, i: &mut syn::StmtMacro) {
+        self.visit_macro(&mut i.mac)
+    }
+
+    // Closures and nested items are not part of the generator body.
+    fn visit_expr_closure_mut(&mut self, _: &mut syn::ExprClosure) {}
+    fn visit_item_mut(&mut self, _: &mut syn::Item) {}
+}
+
+/// Whether `mac` is one of the macros that suspend the generator.
+fn is_site(mac: &syn::Macro) -> bool {
+    mac.path
+        .get_ident()
+        .is_some_and(|ident| is_site_ident(&ident.unraw()))
+}
+
+fn is_site_ident(ident: &syn::Ident) -> bool {
+    ident == "yield" || ident == "yield_batch"
+}
+
+fn contains_site(stream: TokenStream) -> bool {
+    stream.into_iter().any(|tree| match tree {
+        TokenTree::Group(group) => contains_site(group.stream()),
+        TokenTree::Ident(ident) => is_site_ident(&ident.unraw()),
+        _ => false,
+    })
+}
@@ -1,8 +1,15 @@
 use proc_macro::TokenStream;
 
 mod args;
+mod block;
+mod check;
+mod closure;
 mod generator;
+mod into_iter;
+mod introspect;
 mod lifetime;
+mod mac;
+mod maybe_async;
 
 #[proc_macro_attribute]
 pub fn generator(attr: TokenStream, mut item: TokenStream) -> TokenStream {
@@ -10,8 +17,57 @@ pub fn generator(attr: TokenStream, mut item: TokenStream) -> TokenStream {
         Ok(tokens) => tokens.into(),
         Err(e) => {
             let tokens: TokenStream = e.into_compile_error().into();
+            item = generator::strip_arg_attrs(item.into()).into();
             item.extend(tokens);
             item
         }
     }
 }
+
+#[proc_macro]
+pub fn gen_block(input: TokenStream) -> TokenStream {
+    match block::expand(input.into(), block::Kind::Block) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn try_gen(input: TokenStream) -> TokenStream {
+    match block::expand(input.into(), block::Kind::TryGen) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn try_stream(input: TokenStream) -> TokenStream {
+    match block::expand(input.into(), block::Kind::TryStream) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn closure(input: TokenStream) -> TokenStream {
+    match closure::expand(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn into_iter(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match into_iter::expand(attr.into(), item.into(), into_iter::Kind::Iter) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn into_stream(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match into_iter::expand(attr.into(), item.into(), into_iter::Kind::Stream) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
@@ -0,0 +1,158 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+
+/// Whether `name` appears anywhere within `stream`, including within groups.
+///
+/// This is used to skip the macros that can't contain what is being looked for
+/// without having to parse their arguments.
+pub fn contains_ident(stream: TokenStream, name: &str) -> bool {
+    stream.into_iter().any(|tree| match tree {
+        TokenTree::Group(group) => contains_ident(group.stream(), name),
+        TokenTree::Ident(ident) => ident == name,
+        _ => false,
+    })
+}
+
+/// The arguments of a macro invocation whose arguments are made up of
+/// expressions.
+///
+/// Macros can take arbitrary tokens so there's no way to know how they will
+/// be used. This covers the common forms, which is enough to find the `yield`
+/// expressions within most invocations.
+pub enum ExprMacroArgs {
+    /// `a, b, c`, as taken by `println!`, `assert_eq!`, `vec!` and most other
+    /// macros.
+    List(Punctuated<syn::Expr, syn::Token![,]>),
+
+    /// `value; count`, as taken by `vec!`.
+    Repeat {
+        value: syn::Expr,
+        semi_token: syn::Token![;],
+        count: syn::Expr,
+    },
+
+    /// `expr, pattern if guard`, as taken by `matches!`.
+    Matches {
+        expr: syn::Expr,
+        comma_token: syn::Token![,],
+        pat: syn::Pat,
+        guard: Option<(syn::Token![if], syn::Expr)>,
+        trailing: Option<syn::Token![,]>,
+    },
+}
+
+impl ExprMacroArgs {
+    /// Parse the arguments of `mac`, returning `None` if they are not in one
+    /// of the supported forms.
+    pub fn parse(mac: &syn::Macro) -> Option<Self> {
+        let tokens = mac.tokens.clone();
+
+        if mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "matches")
+        {
+            return Self::parse_matches.parse2(tokens).ok();
+        }
+
+        if let Ok(list) = Punctuated::parse_terminated.parse2(tokens.clone()) {
+            return Some(Self::List(list));
+        }
+
+        Self::parse_repeat.parse2(tokens).ok()
+    }
+
+    fn parse_repeat(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self::Repeat {
+            value: input.parse()?,
+            semi_token: input.parse()?,
+            count: input.parse()?,
+        })
+    }
+
+    fn parse_matches(input: ParseStream) -> syn::Result<Self> {
+        let expr = input.parse()?;
+        let comma_token = input.parse()?;
+        let pat = syn::Pat::parse_multi_with_leading_vert(input)?;
+        let guard = match input.parse::<Option<syn::Token![if]>>()? {
+            Some(if_token) => Some((if_token, input.parse()?)),
+            None => None,
+        };
+
+        Ok(Self::Matches {
+            expr,
+            comma_token,
+            pat,
+            guard,
+            trailing: input.parse()?,
+        })
+    }
+
+    /// Visit the expressions within the arguments of `mac` and write them back
+    /// into it.
+    ///
+    /// Returns `false`, leaving `mac` as is, if the arguments are not in one of
+    /// the supported forms.
+    pub fn visit_mut<V: VisitMut + ?Sized>(mac: &mut syn::Macro, visitor: &mut V) -> bool {
+        let Some(mut args) = Self::parse(mac) else {
+            return false;
+        };
+
+        for expr in args.exprs_mut() {
+            visitor.visit_expr_mut(expr);
+        }
+
+        mac.tokens = args.into_token_stream();
+        true
+    }
+
+    /// The expressions within the arguments.
+    pub fn exprs_mut(&mut self) -> Vec<&mut syn::Expr> {
+        match self {
+            Self::List(list) => list.iter_mut().collect(),
+            Self::Repeat { value, count, .. } => vec![value, count],
+            Self::Matches { expr, guard, .. } => {
+                let mut exprs = vec![expr];
+                exprs.extend(guard.as_mut().map(|(_, guard)| guard));
+                exprs
+            }
+        }
+    }
+}
+
+impl ToTokens for ExprMacroArgs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::List(list) => list.to_tokens(tokens),
+            Self::Repeat {
+                value,
+                semi_token,
+                count,
+            } => {
+                value.to_tokens(tokens);
+                semi_token.to_tokens(tokens);
+                count.to_tokens(tokens);
+            }
+            Self::Matches {
+                expr,
+                comma_token,
+                pat,
+                guard,
+                trailing,
+            } => {
+                expr.to_tokens(tokens);
+                comma_token.to_tokens(tokens);
+                pat.to_tokens(tokens);
+                if let Some((if_token, guard)) = guard {
+                    if_token.to_tokens(tokens);
+                    guard.to_tokens(tokens);
+                }
+                trailing.to_tokens(tokens);
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use proc_macro2::TokenStream;
+use syn::ext::IdentExt;
+use syn::visit_mut::{self, VisitMut};
+use syn::Result;
+
+use crate::args::Args;
+use crate::generator::expand_fn;
+use crate::mac::{contains_ident, ExprMacroArgs};
+
+/// Input:
+/// ```ignore
+/// #[generator(maybe_async, yield = A)]
+/// async? fn some_fn(x: u32) { ... }
+/// ```
+///
+/// Output:
+/// ```ignore
+/// #[generator(yield = A)]
+/// fn some_fn(x: u32) { /* the body made sync by `StripAwait` */ }
+///
+/// #[generator(yield = A)]
+/// async fn some_fn_async(x: u32) { ... }
+/// ```
+pub fn expand(args: &Args, maybe_async: &syn::Ident, func: syn::ItemFn) -> Result<TokenStream> {
+    if let Some(recursive) = &args.recursive {
+        return Err(syn::Error::new_spanned(
+            maybe_async,
+            format!("`{recursive}` generators cannot be `{maybe_async}`"),
+        ));
+    }
+
+    let mut sync_fn = func.clone();
+    sync_fn.sig.asyncness = None;
+
+    let mut strip = StripAwait::default();
+    strip.visit_block_mut(&mut sync_fn.block);
+    let is_sync = strip.errors.is_empty();
+    let errors = strip.errors.into_iter().map(syn::Error::into_compile_error);
+
+    let mut async_fn = func;
+    async_fn.sig.asyncness = Some(Default::default());
+    async_fn.sig.ident = syn::Ident::new(
+        &format!("{}_async", async_fn.sig.ident.unraw()),
+        async_fn.sig.ident.span(),
+    );
+
+    // The body is the same for both so only one of them reports problems with it.
+    // If the body couldn't be made sync then the sync version would report every
+    // `.await` that was left in it as well, so the async version is used instead.
+    let sync_fn = expand_fn(args, sync_fn, is_sync)?;
+    let async_fn = expand_fn(args, async_fn, !is_sync)?;
+
+    Ok(quote::quote! {
+        #( #errors )*
+        #sync_fn
+        #async_fn
+    })
+}
+
+/// Makes a generator body sync by following the naming convention used by
+/// `maybe_async` itself: `foo_async(...).await` becomes `foo(...)` and
+/// `x.foo_async(...).await` becomes `x.foo(...)`.
+///
+/// Anything else that is awaited, along with async blocks and closures, has no
+/// sync counterpart and is reported as an error instead.
+#[derive(Default)]
+struct StripAwait {
+    errors: Vec<syn::Error>,
+}
+
+impl StripAwait {
+    /// Remove the `.await`s within the arguments of a macro.
+    ///
+    /// This only works for macros whose arguments are made up of expressions
+    /// (see [`ExprMacroArgs`]). An `.await` within any other macro is an error.
+    fn visit_macro_args(&mut self, mac: &mut syn::Macro) {
+        if !contains_ident(mac.tokens.clone(), "await") {
+            return;
+        }
+
+        if !ExprMacroArgs::visit_mut(mac, self) {
+            self.errors.push(syn::Error::new_spanned(
+                &mac.tokens,
+                "`.await` within this macro can't be removed for the sync version of a \
+                 `maybe_async` generator",
+            ));
+        }
+    }
+}
+
+impl VisitMut for StripAwait {
+    fn visit_expr_mut(&mut self, i: &mut syn::Expr) {
+        match i {
+            syn::Expr::Await(expr) => {
+                let mut base = (*expr.base).clone();
+                self.visit_expr_mut(&mut base);
+
+                let name = match &mut base {
+                    syn::Expr::Call(call) => match &mut *call.func {
+                        syn::Expr::Path(path) => {
+                            path.path.segments.last_mut().map(|seg| &mut seg.ident)
+                        }
+                        _ => None,
+                    },
+                    syn::Expr::MethodCall(call) => Some(&mut call.method),
+                    _ => None,
+                };
+
+                match name.and_then(|name| Some((strip_async_suffix(name)?, name))) {
+                    Some((sync_name, name)) => {
+                        *name = sync_name;
+                        *i = base;
+                    }
+                    None => self.errors.push(syn::Error::new(
+                        expr.await_token.span,
+                        "only calls to functions and methods named `*_async` can be awaited \
+                         within a `maybe_async` generator, the sync version calls the one \
+                         without the suffix instead",
+                    )),
+                }
+            }
+            syn::Expr::Async(expr) => self.errors.push(syn::Error::new_spanned(
+                expr.async_token,
+                "async blocks can't be made sync within a `maybe_async` generator",
+            )),
+            syn::Expr::Closure(closure) => match &closure.asyncness {
+                Some(asyncness) => self.errors.push(syn::Error::new_spanned(
+                    asyncness,
+                    "async closures can't be made sync within a `maybe_async` generator",
+                )),
+                None => visit_mut::visit_expr_closure_mut(self, closure),
+            },
+            _ => visit_mut::visit_expr_mut(self, i),
+        }
+    }
+
+    fn visit_expr_macro_mut(&mut self, i: &mut syn::ExprMacro) {
+        self.visit_macro_args(&mut i.mac)
+    }
+
+    fn visit_stmt_macro_mut(&mut self, i: &mut syn::StmtMacro) {
+        self.visit_macro_args(&mut i.mac)
+    }
+
+    // Nested items are not part of the generator.
+    fn visit_item_mut(&mut self, _: &mut syn::Item) {}
+}
+
+/// `foo_async` -> `foo`
+fn strip_async_suffix(ident: &syn::Ident) -> Option<syn::Ident> {
+    let name = ident.unraw().to_string();
+    let sync_name = name
+        .strip_suffix("_async")
+        .filter(|name| !name.is_empty())?;
+
+    Some(syn::Ident::new(sync_name, ident.span()))
+}
@@ -0,0 +1,43 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::export::{AsyncGenerator, SyncGenerator};
+
+/// The future of a generator whose state is stored on the heap.
+type BoxedFuture<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// The future of a generator whose state is stored on the heap and which can
+/// be sent to another thread.
+type BoxedSendFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// A sync generator whose state is stored on the heap.
+///
+/// Unlike the generators returned by a [`generator`](crate::generator)
+/// function, this type can be named. This makes it usable as an associated
+/// type, which is what [`into_iter`](crate::into_iter) uses it for.
+///
+/// The generator is [`Unpin`], so it can be used as an iterator directly. It
+/// is never [`Send`], see [`BoxedSendIter`] for one that is.
+pub type BoxedIter<'a, Y> = SyncGenerator<BoxedFuture<'a>, Y, ()>;
+
+/// An async generator whose state is stored on the heap.
+///
+/// This is the async version of [`BoxedIter`], as returned by methods
+/// declared with [`into_stream`](crate::into_stream). It is [`Unpin`], so it
+/// can be used as a stream directly.
+///
+/// It is never [`Send`], so it can't be spawned onto a multi-threaded runtime.
+/// See [`BoxedSendStream`] for one that can.
+pub type BoxedStream<'a, Y> = AsyncGenerator<BoxedFuture<'a>, Y, ()>;
+
+/// A version of [`BoxedIter`] that is [`Send`] when `Y` is.
+///
+/// This is returned by methods declared with `#[into_iter(send)]`, whose body
+/// must then be [`Send`] as well.
+pub type BoxedSendIter<'a, Y> = SyncGenerator<BoxedSendFuture<'a>, Y, ()>;
+
+/// A version of [`BoxedStream`] that is [`Send`] when `Y` is.
+///
+/// This is returned by methods declared with `#[into_stream(send)]`, whose
+/// body must then be [`Send`] as well.
+pub type BoxedSendStream<'a, Y> = AsyncGenerator<BoxedSendFuture<'a>, Y, ()>;
@@ -0,0 +1,111 @@
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+
+use crate::detail::{GeneratorWaker, TokenId, WakerState};
+
+/// A type-erased pointer to an iterator of values yielded in a single batch.
+///
+/// The iterator itself lives within the generator future, which is suspended
+/// for as long as the batch is being consumed. This allows the wrapper to pull
+/// values out of it without resuming the future or knowing its type.
+#[derive(Copy, Clone)]
+pub(crate) struct RawBatch {
+    iter: *mut (),
+    next: unsafe fn(*mut (), *mut ()),
+}
+
+impl RawBatch {
+    /// Create a `RawBatch` pointing at `iter`.
+    ///
+    /// The returned batch must not be used after `iter` is dropped.
+    fn new<I: Iterator>(iter: Pin<&mut BatchIter<I>>) -> Self {
+        unsafe fn next<I: Iterator>(iter: *mut (), out: *mut ()) {
+            let iter = &mut *(iter as *mut I);
+            *(out as *mut Option<I::Item>) = iter.next();
+        }
+
+        // SAFETY: We never move the iterator out of the pinned BatchIter.
+        let iter = unsafe { &mut iter.get_unchecked_mut().iter };
+
+        Self {
+            iter: iter as *mut I as *mut (),
+            next: next::<I>,
+        }
+    }
+
+    /// Take the next value from the batch.
+    ///
+    /// # Safety
+    /// - The iterator this batch points to must still be alive.
+    /// - `Y` must be the item type of the iterator.
+    #[inline]
+    pub unsafe fn next<Y>(&self) -> Option<Y> {
+        let mut value = None;
+        (self.next)(self.iter, &mut value as *mut Option<Y> as *mut ());
+        value
+    }
+
+    fn points_to<I>(&self, iter: &BatchIter<I>) -> bool {
+        std::ptr::eq(self.iter, &iter.iter as *const I as *const ())
+    }
+}
+
+// SAFETY: The iterator is owned by the generator future, so it is only sent to
+//         another thread along with the future. It is only accessed through the
+//         wrapper, which requires exclusive access to do so.
+unsafe impl Send for RawBatch {}
+unsafe impl Sync for RawBatch {}
+
+/// Iterator storage for a batch that is currently being yielded.
+///
+/// This is pinned within the generator future while the batch is being
+/// consumed. If it is dropped before the batch has been consumed (e.g. because
+/// the future yielding it was cancelled) then it unregisters itself so the
+/// wrapper doesn't touch it afterwards.
+pub(crate) struct BatchIter<I> {
+    iter: I,
+
+    /// The token of the generator the batch was registered with. Invalid until
+    /// the batch is registered.
+    token: TokenId,
+    _pinned: PhantomPinned,
+}
+
+impl<I: Iterator> BatchIter<I> {
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            token: TokenId::invalid(),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    /// Register this iterator as the current batch of the generator that owns
+    /// `state`, whose token is `token`.
+    pub fn register(mut self: Pin<&mut Self>, state: &mut WakerState, token: TokenId) {
+        state.batch = Some(RawBatch::new(self.as_mut()));
+
+        // SAFETY: We don't move anything out of self.
+        unsafe { self.get_unchecked_mut().token = token };
+    }
+}
+
+impl<I> Drop for BatchIter<I> {
+    fn drop(&mut self) {
+        if !self.token.is_valid() {
+            return;
+        }
+
+        // If the whole generator is being dropped then there is no current state to
+        // unregister from, and nothing will use the batch again anyway.
+        let Some(state) = GeneratorWaker::current_state(self.token) else {
+            return;
+        };
+
+        // SAFETY: The state belongs to the generator being polled on this thread.
+        let state = unsafe { &mut *state };
+        if state.batch.is_some_and(|batch| batch.points_to(self)) {
+            state.batch = None;
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+use crate::YieldSite;
+
+/// Static information about where a generator was defined.
+///
+/// This is emitted by the [`generator`](crate::generator) and
+/// [`gen!`](crate::gen!) macros so that panic messages and `Debug` output can
+/// say which generator they refer to.
+#[derive(Debug)]
+pub struct GeneratorInfo {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub column: u32,
+
+    /// The yield sites of the generator if it was declared with `introspect`.
+    pub sites: &'static [YieldSite],
+}
+
+impl GeneratorInfo {
+    /// The location of the generator in `file:line:column` format.
+    pub(crate) fn location(&self) -> impl fmt::Display + '_ {
+        struct Location<'a>(&'a GeneratorInfo);
+
+        impl fmt::Display for Location<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}:{}:{}", self.0.file, self.0.line, self.0.column)
+            }
+        }
+
+        Location(self)
+    }
+}
+
+impl fmt::Display for GeneratorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` at {}", self.name, self.location())
+    }
+}
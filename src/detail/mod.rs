@@ -1,20 +1,30 @@
 //! Implementation details for generators.
 
+mod batch;
 mod future;
+mod info;
+mod state;
 mod token;
 mod util;
 mod waker;
 mod wrapper;
 
+pub(crate) use self::batch::{BatchIter, RawBatch};
 pub(crate) use self::token::TokenId;
-pub(crate) use self::waker::GeneratorWaker;
+pub(crate) use self::waker::{CurrentGuard, GeneratorWaker, WakerState};
 pub(crate) use self::wrapper::GeneratorWrapper;
 
 #[allow(dead_code)]
 fn stop_import_reordering() {}
 
+pub use self::info::GeneratorInfo;
+pub use self::state::{inline_budget, BoundedState};
 pub use self::token::{RawGeneratorToken, TokenMarker};
 
+/// The bounds on the number of values a generator has left to yield, in the
+/// same form as [`Iterator::size_hint`].
+pub(crate) type SizeHint = (usize, Option<usize>);
+
 pub(crate) enum GeneratorArg<Y, A> {
     Yield(Y),
     Arg(A),
@@ -22,6 +32,14 @@ pub(crate) enum GeneratorArg<Y, A> {
 }
 
 impl<Y, A> GeneratorArg<Y, A> {
+    pub fn is_yield(&self) -> bool {
+        matches!(self, Self::Yield(_))
+    }
+
+    pub fn is_arg(&self) -> bool {
+        matches!(self, Self::Arg(_))
+    }
+
     pub fn take_yield(&mut self) -> Option<Y> {
         match std::mem::replace(self, Self::Empty) {
             Self::Yield(val) => Some(val),
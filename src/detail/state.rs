@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::marker::{PhantomData, PhantomPinned};
+use std::mem::{align_of, size_of, ManuallyDrop, MaybeUninit};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Space for a future stored inline within a [`BoundedState`].
+#[derive(Copy, Clone)]
+#[repr(C, align(8))]
+struct Inline<const N: usize>([MaybeUninit<u8>; N]);
+
+union Storage<F, const N: usize> {
+    inline: Inline<N>,
+    boxed: ManuallyDrop<Pin<Box<F>>>,
+}
+
+/// The state of a generator declared with both `boxed_state` and `max_size`.
+///
+/// The future is stored inline if it fits within `N` bytes and on the heap
+/// otherwise. Which one is decided when the generator type is instantiated,
+/// so there is no need to check at runtime.
+pub struct BoundedState<F, const N: usize> {
+    storage: Storage<F, N>,
+    _marker: PhantomData<F>,
+
+    // The future may be stored inline, in which case it must not be moved once it
+    // has been polled.
+    _pin: PhantomPinned,
+}
+
+impl<F, const N: usize> BoundedState<F, N> {
+    const INLINE: bool = size_of::<F>() <= N && align_of::<F>() <= align_of::<Inline<N>>();
+
+    pub fn new(future: F) -> Self {
+        let storage = match Self::INLINE {
+            true => {
+                let mut inline = Inline([MaybeUninit::uninit(); N]);
+                // SAFETY: INLINE checks that the future fits within the buffer.
+                unsafe { inline.0.as_mut_ptr().cast::<F>().write(future) };
+                Storage { inline }
+            }
+            false => Storage {
+                boxed: ManuallyDrop::new(Box::pin(future)),
+            },
+        };
+
+        Self {
+            storage,
+            _marker: PhantomData,
+            _pin: PhantomPinned,
+        }
+    }
+
+    /// A pointer to the future.
+    ///
+    /// This goes through raw pointers so that the future is never borrowed as
+    /// part of the buffer it is stored in, since it may hold references into
+    /// itself.
+    ///
+    /// # Safety
+    /// `this` must point to a live `BoundedState`.
+    unsafe fn future(this: *mut Self) -> *mut F {
+        match Self::INLINE {
+            true => std::ptr::addr_of_mut!((*this).storage.inline).cast(),
+            false => {
+                let boxed = &mut *std::ptr::addr_of_mut!((*this).storage.boxed);
+                boxed.as_mut().get_unchecked_mut()
+            }
+        }
+    }
+}
+
+impl<F, const N: usize> Future for BoundedState<F, N>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: The future is pinned along with self, or by its box.
+        unsafe {
+            let future = Self::future(self.get_unchecked_mut());
+            Pin::new_unchecked(&mut *future).poll(cx)
+        }
+    }
+}
+
+impl<F, const N: usize> Drop for BoundedState<F, N> {
+    fn drop(&mut self) {
+        // SAFETY: The storage always holds a future of the kind given by INLINE.
+        unsafe {
+            match Self::INLINE {
+                true => std::ptr::drop_in_place(Self::future(self)),
+                false => ManuallyDrop::drop(&mut self.storage.boxed),
+            }
+        }
+    }
+}
+
+/// The number of bytes available to the future of a generator with both
+/// `boxed_state` and `max_size`.
+///
+/// This leaves room for the rest of the generator and is a multiple of the
+/// alignment of the inline storage, so the generator as a whole stays within
+/// `max_size`.
+pub const fn inline_budget(max_size: usize) -> usize {
+    let overhead = size_of::<crate::export::SyncGenerator<(), (), ()>>();
+    let align = align_of::<Inline<0>>();
+
+    max_size.saturating_sub(overhead) / align * align
+}
@@ -1,36 +1,41 @@
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::task::{Context, Poll};
 
 use crate::detail::future::with_context;
 use crate::detail::waker::GeneratorWaker;
-use crate::detail::GeneratorArg;
+use crate::detail::{BatchIter, GeneratorArg, GeneratorInfo};
 use crate::export::{AsyncGenerator, SyncGenerator};
-use crate::GeneratorToken;
+use crate::introspect::SiteTracker;
+use crate::{Error, GeneratorToken, YieldSite};
 
 used_in_docs!(SyncGenerator, AsyncGenerator, GeneratorToken);
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub(crate) struct TokenId(*const ());
+pub(crate) struct TokenId(u64);
 
 impl TokenId {
     pub fn invalid() -> Self {
-        Self(std::ptr::null())
+        Self(0)
     }
 
-    fn new(value: *const ()) -> Self {
-        Self(value)
+    /// Create an id that is different from every other one created so far.
+    ///
+    /// Ids are never reused, so a token that outlives its generator can't be
+    /// mistaken for the token of a generator created later on.
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
     }
 
     pub fn is_valid(self) -> bool {
-        !self.0.is_null()
+        self.0 != 0
     }
 }
 
-unsafe impl Send for TokenId {}
-unsafe impl Sync for TokenId {}
-
 /// Marker type used to ensure that the generator wrapper and the generator
 /// token share the same yield and argument types.
 ///
@@ -41,7 +46,7 @@ unsafe impl Sync for TokenId {}
 /// marker type to bridge the gap.
 ///
 /// [`gen!`]: crate::gen!
-pub struct TokenMarker<Y, A>(PhantomData<(Y, A)>);
+pub struct TokenMarker<Y, A>(PhantomData<fn(Y) -> A>);
 
 impl<Y, A> Default for TokenMarker<Y, A> {
     fn default() -> Self {
@@ -49,34 +54,58 @@ impl<Y, A> Default for TokenMarker<Y, A> {
     }
 }
 
+impl<Y, A> Clone for TokenMarker<Y, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Y, A> Copy for TokenMarker<Y, A> {}
+
 impl<Y, A> TokenMarker<Y, A> {
     pub const fn new() -> Self {
         Self(PhantomData)
     }
+
+    /// Create a new [`RawGeneratorToken`] with the same `Y` and `A` parameters
+    /// as this marker.
+    pub fn token(self, info: &'static GeneratorInfo) -> RawGeneratorToken<Y, A> {
+        RawGeneratorToken::new(info)
+    }
 }
 
 pub struct RawGeneratorToken<Y, A> {
     // In order for this crate to guarantee correctness we need to ensure that each
-    // `GeneratorToken` has a unique address. However, the token doesn't actually store any data so
-    // we have a dummy u8 variable here to ensure it is not zero-sized.
-    _filler: u8,
-    _marker: PhantomData<(Y, A)>,
+    // `GeneratorToken` can be told apart from every other one, which is what the
+    // id is for.
+    id: TokenId,
+    info: &'static GeneratorInfo,
+
+    // The token never stores a `Y` or an `A`, it only passes them through to the
+    // `GeneratorArg` owned by the wrapper. Using a function pointer here means the
+    // token is always `Send` and `Sync`. This is sound because the token can only
+    // reach the generator that is being polled on the current thread, see
+    // `GeneratorWaker::from_waker_ref`.
+    _marker: PhantomData<fn(Y) -> A>,
 }
 
 impl<Y, A> RawGeneratorToken<Y, A> {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(info: &'static GeneratorInfo) -> Self {
         Self {
-            _filler: 0,
+            id: TokenId::next(),
+            info,
             _marker: PhantomData,
         }
     }
 
+    /// Information about the generator that this token was created for.
+    pub(crate) fn info(&self) -> &'static GeneratorInfo {
+        self.info
+    }
+
     /// Returns the unique [`TokenId`] for this `GeneratorToken`.
-    ///
-    /// The token id will be unique for this generator as long as this token is
-    /// still alive.
     pub(crate) fn id(self: Pin<&Self>) -> TokenId {
-        TokenId::new(self.get_ref() as *const _ as _)
+        self.id
     }
 
     /// Returns a [`TokenMarker`] that shares the same `Y` and `A` parameters as
@@ -99,39 +128,92 @@ impl<Y, A> RawGeneratorToken<Y, A> {
     /// - Panics if the waker for this function is not a [`GeneratorWaker`]
     /// - Panics if the waker already has a token registered.
     pub async unsafe fn register(self: Pin<&Self>) {
+        if let Err(e) = self.try_register().await {
+            e.panic(self.info);
+        }
+    }
+
+    /// Register this token with the current generator, returning an error
+    /// instead of panicking.
+    ///
+    /// # Safety
+    /// See [`register`](Self::register).
+    pub async unsafe fn try_register(self: Pin<&Self>) -> Result<(), Error> {
         with_context(|cx| {
-            let waker = match GeneratorWaker::from_waker_ref(cx.waker()) {
-                Some(waker) => waker,
-                None => panic!("called GeneratorToken::register with unsupported waker"),
-            };
+            let waker =
+                GeneratorWaker::from_waker_ref(cx.waker()).ok_or(Error::UnsupportedWaker)?;
 
-            waker.set_id(self.id());
+            waker.set_id(self.id())
         })
         .await
     }
 
     /// Yield a value from the current generator.
-    pub async fn yield_(self: Pin<&Self>, value: Y) -> A {
+    ///
+    /// This is the hot path for every yield so it returns a named future
+    /// instead of going through an `async fn`.
+    pub fn yield_(self: Pin<&Self>, value: Y) -> Yield<'_, Y, A> {
+        Yield(YieldFuture::new(value, self))
+    }
+
+    pub async fn try_yield_(self: Pin<&Self>, value: Y) -> Result<A, Error> {
         YieldFuture::new(value, self).await
     }
 
     pub async fn argument(self: Pin<&Self>) -> A {
-        with_context(|cx| {
-            let waker = match GeneratorWaker::from_waker_ref(cx.waker()) {
-                Some(waker) => waker,
-                None => panic!("called GeneratorToken::arg with unsupported waker"),
-            };
+        match self.try_argument().await {
+            Ok(arg) => arg,
+            Err(e) => e.panic(self.info),
+        }
+    }
 
-            let arg = unsafe { &mut *waker.arg_raw(self) };
-            arg.take_arg().expect("no argument present when resuming")
+    pub async fn set_size_hint(self: Pin<&Self>, lower: usize, upper: Option<usize>) {
+        let result =
+            with_context(|cx| GeneratorWaker::set_size_hint(cx.waker(), self.id(), (lower, upper)))
+                .await;
+
+        if let Err(e) = result {
+            e.panic(self.info);
+        }
+    }
+
+    pub async fn try_argument(self: Pin<&Self>) -> Result<A, Error> {
+        with_context(|cx| {
+            let arg = unsafe { &mut *GeneratorWaker::arg_raw(cx.waker(), self)? };
+            arg.take_arg().ok_or(Error::NoArgument)
         })
         .await
     }
 }
 
+impl<Y> RawGeneratorToken<Y, ()> {
+    /// Yield every value of `iter` from the current generator while only
+    /// suspending it once.
+    pub fn yield_batch<I>(self: Pin<&Self>, iter: I) -> YieldBatch<'_, I::IntoIter, Y>
+    where
+        I: IntoIterator<Item = Y>,
+    {
+        YieldBatch {
+            iter: BatchIter::new(iter.into_iter()),
+            token: self,
+            registered: false,
+            site: SiteTracker::default(),
+        }
+    }
+}
+
 struct YieldFuture<'t, Y, A> {
     value: Option<Y>,
     token: Pin<&'t RawGeneratorToken<Y, A>>,
+
+    // Set once the value has been handed over to the wrapper, which means that
+    // we hold the right to yield until we have received our argument.
+    //
+    // The state is looked up again whenever the claim is released rather than
+    // keeping a pointer to it. Generators whose future is `Unpin` can be moved
+    // between resumes, and the state moves along with them.
+    claimed: bool,
+    site: SiteTracker,
 }
 
 impl<'t, Y, A> YieldFuture<'t, Y, A> {
@@ -139,27 +221,201 @@ impl<'t, Y, A> YieldFuture<'t, Y, A> {
         Self {
             value: Some(value),
             token,
+            claimed: false,
+            site: SiteTracker::default(),
         }
     }
 }
 
 impl<Y, A> Future for YieldFuture<'_, Y, A> {
-    type Output = A;
+    type Output = Result<A, Error>;
 
+    #[inline]
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let waker = GeneratorWaker::from_waker_ref(cx.waker())
-            .expect("called GeneratorToken::yield with unsupported waker");
+        let waker = match GeneratorWaker::checked(cx.waker(), self.token.id()) {
+            Ok(waker) => waker,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
 
-        let arg = unsafe { &mut *waker.arg_raw(self.token.as_ref()) };
+        // SAFETY: The waker was checked against our token so the types match, and it
+        //         is only valid while the generator is being polled.
+        let arg = unsafe { &mut *waker.arg::<Y, A>() };
+        let state = unsafe { &mut *waker.state() };
 
-        match self.value.take() {
-            Some(value) => {
-                *arg = GeneratorArg::Yield(value);
-                Poll::Pending
+        let Some(value) = self.value.take() else {
+            // The wrapper hasn't taken our value yet. This happens when a combinator
+            // polls us again within the same poll of the generator.
+            if arg.is_yield() {
+                wake_wrapped(cx);
+                return Poll::Pending;
+            }
+
+            if std::mem::take(&mut self.claimed) {
+                state.release();
+                self.site.resume();
+            }
+
+            return Poll::Ready(arg.take_arg().ok_or(Error::NoArgument));
+        };
+
+        // Another future within the generator may already be yielding a value. We
+        // wait for it to receive its argument before handing over our own.
+        if !state.is_idle(cx.waker()) {
+            self.value = Some(value);
+            return Poll::Pending;
+        }
+
+        // The value in the slot belongs to a yield that was cancelled after handing
+        // it over. The wrapper takes it when we return so we go after that.
+        if arg.is_yield() {
+            self.value = Some(value);
+            wake_wrapped(cx);
+            return Poll::Pending;
+        }
+
+        // The argument in the slot was passed back for a yield that was cancelled.
+        // Handing over our value would drop it, so it is left for `argument()`.
+        // Nothing is lost if the argument is `()`, so that is dropped as before.
+        if state.is_orphaned() && arg.is_arg() && carries_data::<A>() {
+            return Poll::Ready(Err(Error::UnreadArgument));
+        }
+
+        state.claim();
+        self.claimed = true;
+        self.site.suspend(&mut state.site);
+        *arg = GeneratorArg::Yield(value);
+
+        wake_wrapped(cx);
+        Poll::Pending
+    }
+}
+
+impl<Y, A> Drop for YieldFuture<'_, Y, A> {
+    fn drop(&mut self) {
+        // If we are dropped while waiting for our argument then other yields must not
+        // wait on us any longer.
+        if self.claimed {
+            // SAFETY: The state belongs to the generator being polled on this thread.
+            //         If this future is being dropped along with the rest of the
+            //         generator then there is nothing left to release.
+            if let Some(state) = GeneratorWaker::current_state(self.token.id()) {
+                unsafe { (*state).cancel() };
             }
-            None => Poll::Ready(arg.take_arg().expect("no argument present when resuming")),
         }
     }
 }
 
 impl<Y, A> Unpin for YieldFuture<'_, Y, A> {}
+
+/// Whether dropping a value of type `A` could lose anything.
+#[inline]
+const fn carries_data<A>() -> bool {
+    std::mem::size_of::<A>() != 0 || std::mem::needs_drop::<A>()
+}
+
+/// Combinators only poll futures that have been woken. A yield needs to be
+/// polled again once the generator is resumed so that it can pick up its
+/// argument, so we wake it if the waker belongs to a combinator.
+///
+/// There's no need to do this for the generator's own waker, which would only
+/// cause spurious wakeups.
+#[inline]
+fn wake_wrapped(cx: &Context<'_>) {
+    if !GeneratorWaker::is_direct(cx.waker()) {
+        cx.waker().wake_by_ref();
+    }
+}
+
+/// The future returned by [`RawGeneratorToken::yield_`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Yield<'t, Y, A>(YieldFuture<'t, Y, A>);
+
+impl<Y, A> Yield<'_, Y, A> {
+    /// Record `site` as the place the generator is suspended at while this
+    /// yield is in progress.
+    pub fn at(mut self, site: &'static YieldSite) -> Self {
+        self.0.site.set(site);
+        self
+    }
+}
+
+impl<Y, A> Future for Yield<'_, Y, A> {
+    type Output = A;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.0).poll(cx) {
+            Poll::Ready(Ok(arg)) => Poll::Ready(arg),
+            Poll::Ready(Err(e)) => e.panic(self.0.token.info()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The future returned by [`RawGeneratorToken::yield_batch`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct YieldBatch<'t, I, Y> {
+    iter: BatchIter<I>,
+    token: Pin<&'t RawGeneratorToken<Y, ()>>,
+
+    // Whether the batch has been handed over to the wrapper yet.
+    registered: bool,
+    site: SiteTracker,
+}
+
+impl<I, Y> YieldBatch<'_, I, Y> {
+    /// Record `site` as the place the generator is suspended at while the
+    /// batch is being consumed.
+    pub fn at(mut self, site: &'static YieldSite) -> Self {
+        self.site.set(site);
+        self
+    }
+}
+
+impl<I, Y> Future for YieldBatch<'_, I, Y>
+where
+    I: Iterator<Item = Y>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: We never move the iterator out of self.
+        let this = unsafe { self.get_unchecked_mut() };
+        let info = this.token.info();
+
+        // The first poll hands the batch over to the wrapper. The generator is not
+        // resumed again until the wrapper has taken every value out of it.
+        if !this.registered {
+            let waker = match GeneratorWaker::checked(cx.waker(), this.token.id()) {
+                Ok(waker) => waker,
+                Err(e) => e.panic(info),
+            };
+
+            // Batches wait for any other yields in progress in the same way as
+            // individual values do.
+            let state = unsafe { &mut *waker.state() };
+            let arg = unsafe { &*waker.arg::<Y, ()>() };
+            if !state.is_idle(cx.waker()) {
+                return Poll::Pending;
+            }
+
+            if arg.is_yield() {
+                wake_wrapped(cx);
+                return Poll::Pending;
+            }
+
+            // SAFETY: We never move the iterator out of self.
+            unsafe { Pin::new_unchecked(&mut this.iter).register(state, this.token.id()) };
+            this.registered = true;
+            this.site.suspend(&mut state.site);
+
+            return Poll::Pending;
+        }
+
+        // We don't take the argument here. Generators that yield batches take `()` as
+        // their argument and the wrapper may resume the future several times with
+        // the same argument if a batch turns out to be empty.
+        this.site.resume();
+        Poll::Ready(())
+    }
+}
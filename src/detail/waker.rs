@@ -1,33 +1,232 @@
+use std::cell::Cell;
 use std::pin::Pin;
 use std::ptr::NonNull;
 use std::task::{RawWaker, RawWakerVTable, Waker};
 
 use super::RawGeneratorToken;
-use crate::detail::{GeneratorArg, TokenId};
+use crate::detail::{GeneratorArg, GeneratorInfo, RawBatch, SizeHint, TokenId};
+use crate::{Error, GeneratorStatus, YieldSite};
+
+thread_local! {
+    /// The generator waker for the generator currently being polled on this
+    /// thread.
+    ///
+    /// Combinators like `join!` or `FuturesUnordered` poll their futures with
+    /// a waker of their own that wraps ours. This is used to find the
+    /// generator when that happens.
+    static CURRENT: Cell<*const GeneratorWaker> = const { Cell::new(std::ptr::null()) };
+}
+
+/// State owned by the generator wrapper that the generator's token can
+/// access through the waker.
+pub(crate) struct WakerState {
+    pub info: &'static GeneratorInfo,
+    pub token: TokenId,
+    pub status: GeneratorStatus,
+    pub size_hint: SizeHint,
+    pub batch: Option<RawBatch>,
+
+    /// The site of the last value that was yielded, if the generator was
+    /// declared with `introspect`.
+    pub site: Option<&'static YieldSite>,
+
+    /// Whether a yield has handed its value to the wrapper and is waiting for
+    /// the argument to be passed back in.
+    yielding: bool,
+
+    /// Whether a yield was cancelled after handing its value to the wrapper.
+    /// The next argument passed in belongs to it rather than to the next yield.
+    orphaned: bool,
+
+    /// Counts the yields and batches that have finished. Blocked yields use this
+    /// to tell whether they might be able to go ahead.
+    progress: u64,
+
+    /// The value of `progress` when a yield was last blocked by another one.
+    blocked: Option<u64>,
+
+    /// Wakers for blocked yields that were polled by a combinator with its own
+    /// waker. These are woken once the yield blocking them has finished.
+    waiters: Vec<Waker>,
+}
+
+impl WakerState {
+    pub fn new(info: &'static GeneratorInfo) -> Self {
+        Self {
+            info,
+            token: TokenId::invalid(),
+            status: GeneratorStatus::NotStarted,
+            size_hint: (0, None),
+            batch: None,
+            site: None,
+            yielding: false,
+            orphaned: false,
+            progress: 0,
+            blocked: None,
+            waiters: Vec::new(),
+        }
+    }
+
+    /// Check whether a value can be yielded right now.
+    ///
+    /// Only one yield can be in progress at a time. When several futures
+    /// within the generator yield during the same poll (e.g. within `join!`)
+    /// the first one to be polled goes first and the others wait until it
+    /// has received its argument. If another yield is still in progress then
+    /// this returns false and arranges for the future polled with `waker` to
+    /// be polled again once it has finished.
+    ///
+    /// Single values must then [`claim`](Self::claim) the right to yield.
+    /// Batches don't need to since the batch itself blocks other yields until
+    /// it has been consumed.
+    #[inline]
+    pub fn is_idle(&mut self, waker: &Waker) -> bool {
+        if !self.yielding && self.batch.is_none() {
+            return true;
+        }
+
+        self.block(waker);
+        false
+    }
+
+    /// Claim the right to yield the next value. This must only be called once
+    /// [`is_idle`](Self::is_idle) has returned true.
+    #[inline]
+    pub fn claim(&mut self) {
+        self.yielding = true;
+        self.orphaned = false;
+    }
+
+    #[cold]
+    fn block(&mut self, waker: &Waker) {
+        self.blocked = Some(self.progress);
+
+        // The wrapper polls the generator again itself once the yield is unblocked,
+        // which is enough for combinators that poll with our waker. Those with their
+        // own waker only poll the futures they have been told to.
+        if !GeneratorWaker::is_direct(waker) {
+            self.waiters.push(waker.clone());
+        }
+    }
+
+    /// Release a yield claimed by [`claim`](Self::claim).
+    #[inline]
+    pub fn release(&mut self) {
+        self.yielding = false;
+        self.finish();
+    }
+
+    /// Release a yield that was dropped while it was waiting for its
+    /// argument.
+    #[inline]
+    pub fn cancel(&mut self) {
+        self.orphaned = true;
+        self.release();
+    }
+
+    /// Whether the argument currently passed in belongs to a cancelled yield.
+    #[inline]
+    pub fn is_orphaned(&self) -> bool {
+        self.orphaned
+    }
+
+    /// Mark the current batch as consumed.
+    #[inline]
+    pub fn finish_batch(&mut self) {
+        self.batch = None;
+        self.finish();
+    }
+
+    #[inline]
+    fn finish(&mut self) {
+        self.progress = self.progress.wrapping_add(1);
+
+        if !self.waiters.is_empty() {
+            self.wake_waiters();
+        }
+    }
+
+    #[cold]
+    fn wake_waiters(&mut self) {
+        for waker in self.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Whether a yield was blocked during the last poll and is now able to go
+    /// ahead, meaning that the future should be polled again.
+    ///
+    /// This only returns true if a yield has finished since, so the wrapper
+    /// never spins polling a future that can't make progress.
+    #[inline]
+    pub fn take_unblocked(&mut self) -> bool {
+        self.blocked
+            .take()
+            .is_some_and(|progress| progress != self.progress)
+    }
+
+    /// Forget about any yields that were blocked during a previous poll.
+    #[inline]
+    pub fn clear_blocked(&mut self) {
+        self.blocked = None;
+    }
+}
+
+/// Marks a [`GeneratorWaker`] as the one for the generator currently being
+/// polled on this thread. The previous one is restored when this is dropped.
+pub(crate) struct CurrentGuard {
+    prev: *const GeneratorWaker,
+}
+
+impl CurrentGuard {
+    /// # Safety
+    /// `waker` must remain valid until the returned guard is dropped.
+    #[inline]
+    pub unsafe fn enter(waker: *const GeneratorWaker) -> Self {
+        Self {
+            prev: CURRENT.replace(waker),
+        }
+    }
+}
+
+impl Drop for CurrentGuard {
+    #[inline]
+    fn drop(&mut self) {
+        CURRENT.set(self.prev);
+    }
+}
 
 pub(crate) struct GeneratorWaker {
     waker: Option<NonNull<Waker>>,
-    id: *mut TokenId,
     arg: *mut (),
+    state: *mut WakerState,
 }
 
 impl GeneratorWaker {
     /// Create a new `GeneratorWaker`
     ///
     /// # Safety
-    /// - `waker` must remain valid until this `GeneratorWaker` instance is
-    ///   dropped.
-    /// - `arg` and `id` must remain valid while this `GeneratorWaker` instance
-    ///   is being used to poll futures.
-    pub unsafe fn new<Y, A>(
-        waker: Option<&Waker>,
-        arg: *mut GeneratorArg<Y, A>,
-        id: *mut TokenId,
-    ) -> Self {
+    /// - `waker`, `arg` and `state` must remain valid while this
+    ///   `GeneratorWaker` instance is being used to poll futures.
+    /// - `arg` must point to a `GeneratorArg` whose type matches that of the
+    ///   token that will be registered with this waker.
+    pub unsafe fn new(waker: Option<&Waker>, arg: *mut (), state: *mut WakerState) -> Self {
         Self {
             waker: waker.map(NonNull::from),
-            arg: arg as *mut (),
-            id,
+            arg,
+            state,
+        }
+    }
+
+    /// A `GeneratorWaker` that does not point at anything.
+    ///
+    /// It must be replaced with one created by [`new`](Self::new) before
+    /// being converted to a [`Waker`].
+    pub const fn empty() -> Self {
+        Self {
+            waker: None,
+            arg: std::ptr::null_mut(),
+            state: std::ptr::null_mut(),
         }
     }
 
@@ -42,16 +241,82 @@ impl GeneratorWaker {
         ))
     }
 
+    /// Find the `GeneratorWaker` for the current context.
+    ///
+    /// This is always the generator that is currently being polled on this
+    /// thread. If `waker` is not a `GeneratorWaker` itself then it may be one
+    /// that wraps it (e.g. the waker used by `FuturesUnordered`), so that is
+    /// all we have to go on.
+    ///
+    /// A `GeneratorWaker` can be handed to another thread along with a token,
+    /// which must not be able to move values in or out of a generator that is
+    /// being polled elsewhere. So a `GeneratorWaker` is only accepted if it
+    /// also belongs to the generator being polled on this thread.
+    #[inline]
     pub fn from_waker_ref(waker: &Waker) -> Option<&Self> {
-        if waker.vtable() != &GENERATOR_WAKER_VTABLE {
+        let current = Self::current()?;
+
+        if Self::is_direct(waker) && !std::ptr::eq(waker.data(), current as *const Self as _) {
             return None;
         }
 
-        // SAFETY: We validated the vtable above so we know this points to a
-        //         GeneratorWaker instance.
-        let waker = unsafe { &*(waker.data() as *const Self) };
+        Some(current)
+    }
+
+    /// Whether `waker` is a `GeneratorWaker`, as opposed to a waker that wraps
+    /// one.
+    #[inline]
+    pub fn is_direct(waker: &Waker) -> bool {
+        // GENERATOR_WAKER_VTABLE is a static so comparing addresses is enough. This
+        // avoids comparing each of the function pointers on every yield.
+        std::ptr::eq(waker.vtable(), &GENERATOR_WAKER_VTABLE)
+    }
+
+    #[inline]
+    fn current<'a>() -> Option<&'a Self> {
+        let current = CURRENT.get();
 
-        Some(waker)
+        // SAFETY: CURRENT is only set while a CurrentGuard is alive, which the
+        //         generator wrapper keeps for as long as the waker is valid.
+        unsafe { current.as_ref() }
+    }
+
+    /// The state of the generator currently being polled on this thread, if
+    /// `id` is the token registered with it.
+    ///
+    /// This is for futures within the generator that are dropped while they
+    /// have a yield in progress. If they are dropped during a poll then they
+    /// have to update the state, but if the whole generator is being dropped
+    /// then the state is about to go away and there is nothing to update. The
+    /// state can't be kept from an earlier poll since the generator may have
+    /// been moved since then.
+    pub fn current_state(id: TokenId) -> Option<*mut WakerState> {
+        let waker = Self::current()?;
+
+        // SAFETY: state was guaranteed to be valid when constructing this waker
+        match unsafe { (*waker.state).token } == id {
+            true => Some(waker.state),
+            false => None,
+        }
+    }
+
+    /// Information about the generator currently being polled on this thread.
+    ///
+    /// This is used to name both generators involved when a token is used
+    /// within the wrong one.
+    pub fn current_info() -> Option<&'static GeneratorInfo> {
+        let waker = Self::current()?;
+
+        // SAFETY: state was guaranteed to be valid when constructing this waker
+        Some(unsafe { (*waker.state).info })
+    }
+
+    /// The status of the generator currently being polled on this thread.
+    pub fn current_status() -> Option<GeneratorStatus> {
+        let waker = Self::current()?;
+
+        // SAFETY: state was guaranteed to be valid when constructing this waker
+        Some(unsafe { (*waker.state).status })
     }
 
     fn waker(&self) -> Option<&Waker> {
@@ -71,32 +336,84 @@ impl GeneratorWaker {
         }
     }
 
-    pub(super) fn set_id(&self, id: TokenId) {
-        let waker_id = unsafe { &mut *self.id };
+    pub(super) fn set_id(&self, id: TokenId) -> Result<(), Error> {
+        let waker_id = unsafe { &mut (*self.state).token };
 
         // This ensures that it is only possible to register a token once.
-        assert!(
-            !waker_id.is_valid(),
-            "this generator already has a registered token"
-        );
+        if waker_id.is_valid() {
+            return Err(Error::AlreadyRegistered);
+        }
 
         *waker_id = id;
+        Ok(())
+    }
+
+    /// Access the [`GeneratorArg`] pointer stored within the generator waker
+    /// for the current context.
+    ///
+    /// # Errors
+    /// - Returns an error if `waker` is not a `GeneratorWaker`.
+    /// - Returns an error if `token` is not the token registered with the
+    ///   waker.
+    #[inline]
+    pub fn arg_raw<Y, A>(
+        waker: &Waker,
+        token: Pin<&RawGeneratorToken<Y, A>>,
+    ) -> Result<*mut GeneratorArg<Y, A>, Error> {
+        Self::checked(waker, token.id()).map(|waker| waker.arg.cast())
+    }
+
+    /// The [`GeneratorArg`] pointer stored within this waker.
+    ///
+    /// The waker must have been checked against a `RawGeneratorToken<Y, A>`
+    /// using [`checked`](Self::checked) for the types to be correct.
+    #[inline]
+    pub fn arg<Y, A>(&self) -> *mut GeneratorArg<Y, A> {
+        self.arg.cast()
+    }
+
+    /// The state of the generator that owns this waker.
+    #[inline]
+    pub fn state(&self) -> *mut WakerState {
+        self.state
     }
 
-    /// Access the [`GeneratorArg`] pointer stored within this waker.
+    /// Update the size hint of the generator for the current context.
     ///
-    /// # Panics
-    /// Panics if `token` is not the token registered with this waker.
-    pub fn arg_raw<Y, A>(&self, token: Pin<&RawGeneratorToken<Y, A>>) -> *mut GeneratorArg<Y, A> {
-        // SAFETY: id was guanteed to be valid when constructing this waker
-        if unsafe { *self.id } != token.id() {
-            panic!("waker id does not match generator id");
+    /// # Errors
+    /// Returns an error in the same cases as [`arg_raw`](Self::arg_raw).
+    pub fn set_size_hint(waker: &Waker, id: TokenId, size_hint: SizeHint) -> Result<(), Error> {
+        let waker = Self::checked(waker, id)?;
+
+        // SAFETY: state was guaranteed to be valid when constructing this waker
+        unsafe { (*waker.state).size_hint = size_hint };
+        Ok(())
+    }
+
+    /// Get the `GeneratorWaker` for the current context, checking that `id` is
+    /// the token registered with it.
+    ///
+    /// This runs on every yield. It is small enough that inlining it is both
+    /// faster and smaller than calling it out of line.
+    #[inline]
+    pub fn checked(waker: &Waker, id: TokenId) -> Result<&Self, Error> {
+        let waker = Self::from_waker_ref(waker).ok_or(Error::UnsupportedWaker)?;
+
+        // SAFETY: state was guanteed to be valid when constructing this waker
+        if unsafe { (*waker.state).token } != id {
+            return Err(Error::TokenMismatch);
         }
 
-        self.arg as *mut _
+        Ok(waker)
     }
 }
 
+// SAFETY: The pointers within a GeneratorWaker are only dereferenced while the
+//         generator that owns it is being polled, which requires exclusive access
+//         to the generator.
+unsafe impl Send for GeneratorWaker {}
+unsafe impl Sync for GeneratorWaker {}
+
 unsafe fn waker_clone(ptr: *const ()) -> RawWaker {
     let waker = &*(ptr as *const GeneratorWaker);
     waker.clone_waker()
@@ -1,67 +1,385 @@
+use std::fmt;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll, Waker};
 
-use crate::detail::{GeneratorArg, GeneratorWaker, TokenId};
-use crate::GeneratorState;
+use crate::detail::{
+    CurrentGuard, GeneratorArg, GeneratorInfo, GeneratorWaker, SizeHint, WakerState,
+};
+use crate::{Error, GeneratorState, GeneratorStatus, YieldSite};
 
 pub(crate) struct GeneratorWrapper<F, Y, A> {
+    // The future is dropped before the core, so a batch yielded by the future
+    // never outlives the core that points to it.
     future: F,
-    token: TokenId,
-    _marker: PhantomData<(Y, A)>,
+    core: WrapperCore,
+
+    // Values of `Y` and `A` are only ever passed through the wrapper during a
+    // single call to `poll`. Any that live longer than that are stored within
+    // the future or within the `GeneratorArg` owned by the caller, so they are
+    // already accounted for by the auto traits of those types.
+    _marker: PhantomData<fn(A) -> Y>,
+}
+
+/// The parts of a [`GeneratorWrapper`] that don't depend on its type
+/// parameters.
+///
+/// Every generator type gets its own copy of the generic wrapper methods, so
+/// as much as possible is done here instead. The generic methods are left as
+/// thin shims that only deal with the typed values.
+///
+/// While the future is being polled, the waker stored here points into the
+/// core. Taking a reference to the whole core would invalidate those pointers,
+/// so during a poll the core is only accessed through a single raw pointer
+/// and the methods that are used then take one.
+struct WrapperCore {
+    waker: GeneratorWaker,
+    state: WakerState,
+}
+
+impl WrapperCore {
+    #[inline]
+    fn check_resumable(&self) -> Result<(), Error> {
+        match self.state.status {
+            // Polling a future again after it has panicked may do anything, so we refuse
+            // to do so.
+            GeneratorStatus::Poisoned => Err(Error::Poisoned),
+            GeneratorStatus::Complete => Err(Error::Completed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Panic if the generator cannot be resumed.
+    #[inline]
+    fn assert_resumable(&self) {
+        if let Err(e) = self.check_resumable() {
+            e.panic(self.state.info);
+        }
+    }
+
+    /// Mark the generator as suspended after it has yielded a value.
+    ///
+    /// # Safety
+    /// `core` must be valid for writes.
+    #[inline]
+    unsafe fn yielded(core: *mut Self) {
+        (*core).state.status = GeneratorStatus::Suspended;
+
+        // The size hint counts the values that have yet to be yielded.
+        let (lower, upper) = &mut (*core).state.size_hint;
+        *lower = lower.saturating_sub(1);
+        *upper = upper.map(|upper| upper.saturating_sub(1));
+    }
+
+    fn current_yield_site(&self) -> Option<&'static YieldSite> {
+        match self.state.status {
+            GeneratorStatus::Suspended => self.state.site,
+            _ => None,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.state.status {
+            GeneratorStatus::Complete | GeneratorStatus::Poisoned => (0, Some(0)),
+            _ => self.state.size_hint,
+        }
+    }
+
+    /// Take the next value out of the batch currently being yielded, if there
+    /// is one.
+    ///
+    /// Once the batch runs out it is cleared so that the generator future is
+    /// resumed.
+    ///
+    /// # Safety
+    /// - `core` must be valid for writes.
+    /// - `Y` must be the yield type of the wrapper this core belongs to.
+    #[inline]
+    unsafe fn next_batched<Y>(core: *mut Self) -> Option<Y> {
+        let batch = (*core).state.batch?;
+
+        // The iterator is user code that may panic, in which case the generator is
+        // left poisoned by the `PoisonGuard` in the same way as when polling the
+        // future panics.
+        let status = std::mem::replace(&mut (*core).state.status, GeneratorStatus::Running);
+
+        // SAFETY: The iterator unregisters itself when it is dropped, so the batch
+        //         is still valid if it is present.
+        match batch.next() {
+            Some(value) => {
+                Self::yielded(core);
+                Some(value)
+            }
+            None => {
+                (*core).state.status = status;
+                (*core).state.finish_batch();
+                None
+            }
+        }
+    }
+
+    /// Point the [`GeneratorWaker`] stored in this core at `arg` and return a
+    /// [`Waker`] that refers to it.
+    ///
+    /// This also marks the generator as running. The caller is responsible for
+    /// poisoning it if polling the future panics, using a [`PoisonGuard`].
+    ///
+    /// # Safety
+    /// - `core` must be valid for writes.
+    /// - `waker` and `arg` must remain valid for as long as the returned
+    ///   `Waker` is used to poll futures.
+    /// - The type of the `GeneratorArg` that `arg` points to must match the
+    ///   type parameters of the wrapper this core belongs to.
+    /// - The returned `Waker` must not outlive this core, and the core must
+    ///   only be accessed through `core` while it is in use.
+    #[inline(never)]
+    unsafe fn enter(core: *mut Self, waker: Option<&Waker>, arg: *mut ()) -> Waker {
+        let state = std::ptr::addr_of_mut!((*core).state);
+        let generator_waker = std::ptr::addr_of_mut!((*core).waker);
+
+        generator_waker.write(GeneratorWaker::new(waker, arg, state));
+        (*state).clear_blocked();
+        (*core).state.status = GeneratorStatus::Running;
+
+        // SAFETY: The core is always pinned within the wrapper so the waker will not
+        //         move while the returned `Waker` is in use.
+        Pin::new_unchecked(&*generator_waker).to_waker()
+    }
+
+    /// Make the waker in this core the current one for this thread until the
+    /// returned guard is dropped, so that it can still be found by futures
+    /// polled with a waker that wraps it.
+    ///
+    /// This is kept separate from [`enter`](Self::enter) since returning the
+    /// guard from there makes every resume noticeably slower.
+    ///
+    /// # Safety
+    /// The returned guard must not outlive this core, and the same rules apply
+    /// to `core` as for [`enter`](Self::enter).
+    #[inline]
+    unsafe fn make_current(core: *mut Self) -> CurrentGuard {
+        CurrentGuard::enter(std::ptr::addr_of!((*core).waker))
+    }
+
+    #[inline(never)]
+    fn debug(
+        &self,
+        name: &str,
+        yield_ty: &str,
+        arg_ty: &str,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let mut f = f.debug_struct(name);
+        f.field("name", &self.state.info.name)
+            .field("location", &format_args!("{}", self.state.info.location()))
+            .field("status", &self.state.status);
+
+        if let Some(site) = self.current_yield_site() {
+            f.field("yield_site", &format_args!("{site}"));
+        }
+
+        f.field("yield", &format_args!("{yield_ty}"))
+            .field("arg", &format_args!("{arg_ty}"))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Marks the generator as poisoned if a panic unwinds out of it while it is
+/// running.
+///
+/// This is forgotten once the generator has been polled, so it only does
+/// anything if polling the future panics.
+struct PoisonGuard(*mut WrapperCore);
+
+impl PoisonGuard {
+    #[inline]
+    fn disarm(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for PoisonGuard {
+    fn drop(&mut self) {
+        // SAFETY: The guard never outlives the core it points to, and is only
+        //         dropped while the core is accessed through raw pointers.
+        unsafe {
+            if (*self.0).state.status == GeneratorStatus::Running {
+                (*self.0).state.status = GeneratorStatus::Poisoned;
+            }
+        }
+    }
 }
 
 impl<F, Y, A> GeneratorWrapper<F, Y, A> {
-    pub fn new(future: F) -> Self {
+    pub fn new(info: &'static GeneratorInfo, future: F) -> Self {
         Self {
             future,
-            token: TokenId::invalid(),
+            core: WrapperCore {
+                waker: GeneratorWaker::empty(),
+                state: WakerState::new(info),
+            },
             _marker: PhantomData,
         }
     }
+
+    pub fn status(&self) -> GeneratorStatus {
+        self.core.state.status
+    }
+
+    pub fn current_yield_site(&self) -> Option<&'static YieldSite> {
+        self.core.current_yield_site()
+    }
+
+    pub fn yield_sites(&self) -> &'static [YieldSite] {
+        self.core.state.info.sites
+    }
+
+    /// The bounds on the number of values this generator has left to yield.
+    pub fn size_hint(&self) -> SizeHint {
+        self.core.size_hint()
+    }
+
+    /// Set the size hint before the generator has been resumed.
+    pub fn set_size_hint(&mut self, size_hint: SizeHint) {
+        self.core.state.size_hint = size_hint;
+    }
+
+    /// Shared [`Debug`](fmt::Debug) implementation for the exported generator
+    /// types.
+    pub fn debug(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.core.debug(
+            name,
+            std::any::type_name::<Y>(),
+            std::any::type_name::<A>(),
+            f,
+        )
+    }
 }
 
 impl<F, Y, A, R> GeneratorWrapper<F, Y, A>
 where
     F: Future<Output = R>,
 {
+    /// The core of a pinned wrapper.
+    ///
+    /// Going through `&Self` instead would also borrow the future, which may
+    /// hold references into itself while it is suspended.
+    #[inline]
+    fn core(self: Pin<&mut Self>) -> &mut WrapperCore {
+        // SAFETY: Nothing is moved out of the wrapper.
+        unsafe { &mut self.get_unchecked_mut().core }
+    }
+
+    /// Poll the future once.
+    ///
+    /// Callers must check that the generator is resumable using
+    /// `check_resumable` first. Keeping the checks out of here means the
+    /// panicking resume methods never have to build a `Result` in the common
+    /// case.
+    #[inline]
     fn poll(
         self: Pin<&mut Self>,
         waker: Option<&Waker>,
         arg: &mut GeneratorArg<Y, A>,
     ) -> Poll<GeneratorState<Y, R>> {
         let this = unsafe { self.get_unchecked_mut() };
-        let future = unsafe { Pin::new_unchecked(&mut this.future) };
 
-        // SAFETY: GeneratorWaker's clone impl returns a different waker so it will not
-        //         outlive this function. This ensures that it will not outlive the
-        //         references passed in here.
-        let waker = unsafe { GeneratorWaker::new(waker, arg, &mut this.token) };
-        let waker = std::pin::pin!(waker);
+        // The waker points into the core and at the argument, so all accesses to
+        // either go through these pointers until it is dropped.
+        let core: *mut WrapperCore = &mut this.core;
+        let arg: *mut GeneratorArg<Y, A> = arg;
+        let guard = PoisonGuard(core);
 
-        // SAFETY: waker will not outlive this function.
-        let waker = unsafe { waker.as_ref().to_waker() };
-        let mut context = Context::from_waker(&waker);
-        match future.poll(&mut context) {
-            Poll::Pending => match arg.take_yield() {
-                Some(value) => Poll::Ready(GeneratorState::Yielded(value)),
-                None => Poll::Pending,
-            },
-            Poll::Ready(value) => Poll::Ready(GeneratorState::Complete(value)),
-        }
+        let state = 'poll: {
+            // Values left in the current batch are handed out without resuming the
+            // future.
+            if let Some(value) = unsafe { WrapperCore::next_batched(core) } {
+                break 'poll Poll::Ready(GeneratorState::Yielded(value));
+            }
+
+            let mut future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+            // SAFETY: waker and arg outlive the context, which is dropped at the end
+            //         of this block.
+            let waker = unsafe { WrapperCore::enter(core, waker, arg as _) };
+            let _current = unsafe { WrapperCore::make_current(core) };
+            let mut context = Context::from_waker(&waker);
+
+            loop {
+                match future.as_mut().poll(&mut context) {
+                    Poll::Pending => unsafe {
+                        if let Some(value) = (*arg).take_yield() {
+                            WrapperCore::yielded(core);
+                            break 'poll Poll::Ready(GeneratorState::Yielded(value));
+                        }
+
+                        if (*core).state.batch.is_some() {
+                            match WrapperCore::next_batched(core) {
+                                Some(value) => {
+                                    break 'poll Poll::Ready(GeneratorState::Yielded(value))
+                                }
+                                // An empty batch doesn't yield anything so we carry on.
+                                None => continue,
+                            }
+                        }
+
+                        // A yield within the future had to wait for another one to
+                        // finish and can now go ahead.
+                        if (*core).state.take_unblocked() {
+                            continue;
+                        }
+
+                        // The generator stays running while it waits on another future.
+                        break 'poll Poll::Pending;
+                    },
+                    Poll::Ready(value) => {
+                        unsafe { (*core).state.status = GeneratorStatus::Complete };
+                        break 'poll Poll::Ready(GeneratorState::Complete(value));
+                    }
+                }
+            }
+        };
+
+        guard.disarm();
+        state
     }
 
     /// Resume the generator and run it until it yields a value or returns.
     ///
     /// # Panics
-    /// Panics if the internal generator function returns pending without having
-    /// yielded a value.
-    pub fn resume(self: Pin<&mut Self>, arg: A) -> GeneratorState<Y, R> {
+    /// - Panics if the internal generator function returns pending without
+    ///   having yielded a value.
+    /// - Panics if a previous call to `resume` panicked.
+    /// - Panics if the generator has already completed.
+    pub fn resume(mut self: Pin<&mut Self>, arg: A) -> GeneratorState<Y, R> {
+        self.as_mut().core().assert_resumable();
+
         let mut arg = GeneratorArg::Arg(arg);
-        match self.poll(None, &mut arg) {
-            Poll::Pending => panic!("generator function returned pending without yielding a value"),
+        match self.as_mut().poll(None, &mut arg) {
             Poll::Ready(state) => state,
+            Poll::Pending => {
+                let core = self.core();
+                core.state.status = GeneratorStatus::Poisoned;
+                Error::PendingWithoutYield.panic(core.state.info)
+            }
+        }
+    }
+
+    /// Resume the generator and run it until it yields a value or returns.
+    ///
+    /// This returns an error in the same cases where [`resume`](Self::resume)
+    /// would panic.
+    pub fn try_resume(mut self: Pin<&mut Self>, arg: A) -> Result<GeneratorState<Y, R>, Error> {
+        self.as_mut().core().check_resumable()?;
+
+        let mut arg = GeneratorArg::Arg(arg);
+        match self.as_mut().poll(None, &mut arg) {
+            Poll::Ready(state) => Ok(state),
+            Poll::Pending => {
+                self.core().state.status = GeneratorStatus::Poisoned;
+                Err(Error::PendingWithoutYield)
+            }
         }
     }
 
@@ -69,13 +387,110 @@ where
     ///
     /// This can be any of a yield point, a return, or an internal future being
     /// used.
+    ///
+    /// # Panics
+    /// - Panics if a previous call to `poll_resume` panicked.
+    /// - Panics if the generator has already completed.
     pub fn poll_resume(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         arg: &mut GeneratorArg<Y, A>,
     ) -> Poll<GeneratorState<Y, R>> {
+        self.as_mut().core().assert_resumable();
         self.poll(Some(cx.waker()), arg)
     }
+
+    /// Resume the generator and run it until the next await point.
+    ///
+    /// This returns an error in the same cases where
+    /// [`poll_resume`](Self::poll_resume) would panic.
+    pub fn try_poll_resume(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        arg: &mut GeneratorArg<Y, A>,
+    ) -> Poll<Result<GeneratorState<Y, R>, Error>> {
+        if let Err(e) = self.as_mut().core().check_resumable() {
+            return Poll::Ready(Err(e));
+        }
+
+        self.poll(Some(cx.waker()), arg).map(Ok)
+    }
+}
+
+impl<F, Y, R> GeneratorWrapper<F, Y, ()>
+where
+    F: Future<Output = R>,
+{
+    /// Resume the generator until it completes, folding each yielded value
+    /// into an accumulator.
+    ///
+    /// This behaves the same as calling [`resume`](Self::resume) in a loop but
+    /// the waker and context are only created once, instead of once per
+    /// yield.
+    ///
+    /// # Panics
+    /// Panics in the same cases as [`resume`](Self::resume).
+    pub fn fold<B, G>(self: Pin<&mut Self>, init: B, mut func: G) -> (B, R)
+    where
+        G: FnMut(B, Y) -> B,
+    {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.core.assert_resumable();
+
+        // All accesses to the core and the argument go through these pointers since
+        // the waker holds on to them for the whole loop.
+        let core: *mut WrapperCore = &mut this.core;
+        let mut arg = GeneratorArg::Empty;
+        let arg: *mut GeneratorArg<Y, ()> = &mut arg;
+
+        // A panic within `func` leaves the generator suspended, so only panics from
+        // the generator itself poison it.
+        let guard = PoisonGuard(core);
+        let mut acc = init;
+
+        // The generator may have been partway through a batch already.
+        while let Some(value) = unsafe { WrapperCore::next_batched(core) } {
+            acc = func(acc, value);
+        }
+
+        let mut future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        // SAFETY: arg outlives the context, which is dropped at the end of this
+        //         function.
+        let waker = unsafe { WrapperCore::enter(core, None, arg as _) };
+        let _current = unsafe { WrapperCore::make_current(core) };
+        let mut context = Context::from_waker(&waker);
+
+        loop {
+            unsafe {
+                *arg = GeneratorArg::Arg(());
+                (*core).state.status = GeneratorStatus::Running;
+            }
+
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(value) => {
+                    unsafe { (*core).state.status = GeneratorStatus::Complete };
+                    guard.disarm();
+                    return (acc, value);
+                }
+                Poll::Pending => unsafe {
+                    match (*arg).take_yield() {
+                        Some(value) => {
+                            WrapperCore::yielded(core);
+                            acc = func(acc, value);
+                        }
+                        None if (*core).state.batch.is_some() => {
+                            while let Some(value) = WrapperCore::next_batched(core) {
+                                acc = func(acc, value);
+                            }
+                        }
+                        None if (*core).state.take_unblocked() => (),
+                        None => Error::PendingWithoutYield.panic((*core).state.info),
+                    }
+                },
+            }
+        }
+    }
 }
 
 impl<F, Y, A> Unpin for GeneratorWrapper<F, Y, A> where F: Unpin {}
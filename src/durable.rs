@@ -0,0 +1,244 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::pin::Pin;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Generator, GeneratorState, YieldHint};
+
+type YieldEncoder<Y> = fn(&Y) -> serde_json::Result<Value>;
+
+/// A generator that journals its arguments to disk so that it can be
+/// recovered after a crash.
+///
+/// Every argument passed to [`resume`](Durable::try_resume) is appended to a
+/// journal file before the generator is resumed. After a restart,
+/// [`Durable::recover`] rebuilds the generator by creating a new one and
+/// replaying the journalled arguments into it.
+///
+/// A value yielded by the generator is considered to be acknowledged once the
+/// generator has been resumed again. When recovering, values that were already
+/// acknowledged are skipped while the last value, which may not have been
+/// fully processed before the crash, is handed back to the caller. This gives
+/// at-least-once delivery for every value yielded by the generator.
+///
+/// For recovery to work the generator must be deterministic: resuming it with
+/// the same arguments must result in the same values being yielded. Side
+/// effects should happen in the consumer of the yielded values, not within the
+/// generator itself.
+///
+/// The journal is a file containing one JSON entry per line. Entries are
+/// written directly to the file but are not synced to disk unless
+/// [`sync`](Durable::sync) is called.
+///
+/// # Example
+/// ```
+/// use fauxgen::{Durable, GeneratorState};
+///
+/// #[fauxgen::generator(yield = String, arg = u32)]
+/// fn workflow() {
+///     let mut step = argument!();
+///
+///     loop {
+///         step = r#yield!(format!("run step {step}"));
+///     }
+/// }
+///
+/// # let path = std::env::temp_dir().join(format!("fauxgen-doctest-{}", std::process::id()));
+/// let mut gen = Durable::create(&path, workflow())?;
+/// gen.try_resume(1)?;
+/// gen.try_resume(2)?;
+/// drop(gen);
+///
+/// // ... the process restarts ...
+///
+/// let (mut gen, pending) = Durable::recover(&path, workflow)?;
+/// assert_eq!(pending, Some(GeneratorState::Yielded("run step 2".to_string())));
+/// assert_eq!(gen.try_resume(3)?, GeneratorState::Yielded("run step 3".to_string()));
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Durable<G, A>
+where
+    G: Generator<A>,
+{
+    gen: Pin<Box<G>>,
+    journal: File,
+    encode_yield: Option<YieldEncoder<G::Yield>>,
+    _marker: PhantomData<fn(A)>,
+}
+
+impl<G, A> Durable<G, A>
+where
+    G: Generator<A>,
+    A: Serialize,
+{
+    /// Wrap a newly created generator, creating a new journal at `path`.
+    ///
+    /// If a file already exists at `path` it will be truncated.
+    pub fn create(path: impl AsRef<Path>, gen: G) -> io::Result<Self> {
+        let journal = File::create(path)?;
+
+        Ok(Self {
+            gen: Box::pin(gen),
+            journal,
+            encode_yield: None,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Also record each value yielded by the generator in the journal.
+    ///
+    /// Recorded values are not needed for recovery, but they make the journal
+    /// useful as an audit log. They are ignored by [`recover`], so this needs
+    /// to be called again on the recovered generator to keep recording them.
+    ///
+    /// [`recover`]: Durable::recover
+    pub fn record_yields(mut self) -> Self
+    where
+        G::Yield: Serialize,
+    {
+        self.encode_yield = Some(|value| serde_json::to_value(value));
+        self
+    }
+
+    /// Rebuild a generator from the journal at `path`.
+    ///
+    /// This creates a new generator using `factory` and then resumes it with
+    /// each argument in the journal. The state returned by the final resume is
+    /// returned alongside the generator, since the caller may not have finished
+    /// processing it before the journal was interrupted. It is `None` if the
+    /// journal was empty.
+    ///
+    /// If the last entry in the journal was only partially written then it is
+    /// discarded.
+    ///
+    /// # Errors
+    /// Returns an error if the journal could not be read or if it contains an
+    /// entry that could not be parsed.
+    #[allow(clippy::type_complexity)]
+    pub fn recover<F>(
+        path: impl AsRef<Path>,
+        factory: F,
+    ) -> io::Result<(Self, Option<GeneratorState<G::Yield, G::Return>>)>
+    where
+        F: FnOnce() -> G,
+        A: DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let mut journal = OpenOptions::new().read(true).append(true).open(path)?;
+
+        let mut contents = String::new();
+        journal.read_to_string(&mut contents)?;
+
+        // A crash in the middle of writing an entry will leave a partial line at the
+        // end of the journal. That entry was never acted upon so we drop it.
+        let valid = contents.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        if valid != contents.len() {
+            journal.set_len(valid as u64)?;
+        }
+
+        let mut gen = Box::pin(factory());
+        let mut state = None;
+
+        for line in contents[..valid].lines() {
+            let (kind, value): (String, Value) = serde_json::from_str(line)?;
+
+            match kind.as_str() {
+                "arg" => {
+                    if matches!(state, Some(GeneratorState::Complete(_))) {
+                        return Err(invalid_data("journal continues after generator completed"));
+                    }
+
+                    state = Some(gen.as_mut().resume(serde_json::from_value(value)?));
+                }
+                "yield" => {
+                    if !matches!(state, Some(GeneratorState::Yielded(_))) {
+                        return Err(invalid_data("journal contains a yield without an argument"));
+                    }
+                }
+                _ => return Err(invalid_data(format!("unknown journal entry `{kind}`"))),
+            }
+        }
+
+        let this = Self {
+            gen,
+            journal,
+            encode_yield: None,
+            _marker: PhantomData,
+        };
+
+        Ok((this, state))
+    }
+
+    /// Resume the generator, journalling `arg` first.
+    ///
+    /// # Errors
+    /// Returns an error if writing to the journal fails. The generator is not
+    /// resumed in that case.
+    pub fn try_resume(&mut self, arg: A) -> io::Result<GeneratorState<G::Yield, G::Return>> {
+        self.append("arg", serde_json::to_value(&arg)?)?;
+
+        let state = self.gen.as_mut().resume(arg);
+
+        if let (Some(encode), GeneratorState::Yielded(value)) = (self.encode_yield, &state) {
+            self.append("yield", encode(value)?)?;
+        }
+
+        Ok(state)
+    }
+
+    /// Sync the journal to disk.
+    pub fn sync(&self) -> io::Result<()> {
+        self.journal.sync_data()
+    }
+
+    fn append(&mut self, kind: &str, value: Value) -> io::Result<()> {
+        let mut line = serde_json::to_vec(&(kind, value))?;
+        line.push(b'\n');
+
+        // Entries are written with a single call so that a crash can only leave a
+        // partial entry at the very end of the journal.
+        self.journal.write_all(&line)
+    }
+}
+
+impl<G, A> Generator<A> for Durable<G, A>
+where
+    G: Generator<A>,
+    A: Serialize,
+{
+    type Yield = G::Yield;
+    type Return = G::Return;
+
+    /// Resume the generator, journalling `arg` first.
+    ///
+    /// # Panics
+    /// Panics if writing to the journal fails. Use
+    /// [`try_resume`](Durable::try_resume) to handle these errors instead.
+    fn resume(self: Pin<&mut Self>, arg: A) -> GeneratorState<Self::Yield, Self::Return> {
+        match self.get_mut().try_resume(arg) {
+            Ok(state) => state,
+            Err(e) => panic!("failed to write to generator journal: {e}"),
+        }
+    }
+}
+
+impl<G, A> Unpin for Durable<G, A> where G: Generator<A> {}
+
+impl<G, A> YieldHint for Durable<G, A>
+where
+    G: Generator<A> + YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.gen.yield_hint()
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::detail::{GeneratorInfo, GeneratorWaker};
+
+/// Errors that can occur when a generator or its token is misused.
+///
+/// Most methods on [`GeneratorToken`](crate::GeneratorToken) and the
+/// generator types panic when they encounter one of these. The `try_`
+/// variants of those methods return the error instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The token was used within a future that is not being polled by a
+    /// fauxgen generator.
+    UnsupportedWaker,
+
+    /// A token was registered with a generator that already had one.
+    AlreadyRegistered,
+
+    /// The token was used within a generator other than the one it was
+    /// created for.
+    TokenMismatch,
+
+    /// There was no argument available to take. This happens if the argument
+    /// has already been read, or if the generator was resumed without one.
+    NoArgument,
+
+    /// A yield was cancelled after its value had been yielded, and the
+    /// argument passed back for it had not been read before the generator
+    /// yielded again. The argument is kept and can still be taken with
+    /// `argument()`.
+    UnreadArgument,
+
+    /// The generator was resumed after it panicked.
+    Poisoned,
+
+    /// The generator was resumed after it completed.
+    Completed,
+
+    /// A sync generator awaited on a future that was not one of its yields.
+    PendingWithoutYield,
+}
+
+impl Error {
+    /// Panic with this error, mentioning the generator that it occurred in.
+    ///
+    /// For [`TokenMismatch`](Self::TokenMismatch) `info` is the generator that
+    /// the token belongs to, and the one it was used within is named as well.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn panic(self, info: &GeneratorInfo) -> ! {
+        match (self, GeneratorWaker::current_info()) {
+            (Self::TokenMismatch, Some(current)) => {
+                panic!("{self} (token belongs to {info}, but was used within {current})")
+            }
+            _ => panic!("{self} (in {info})"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnsupportedWaker => "generator token used with an unsupported waker",
+            Self::AlreadyRegistered => "this generator already has a registered token",
+            Self::TokenMismatch => "waker id does not match generator id",
+            Self::NoArgument => "no argument present when resuming",
+            Self::UnreadArgument => {
+                "argument for a cancelled yield was not read before yielding again"
+            }
+            Self::Poisoned => "generator resumed after panicking",
+            Self::Completed => "generator resumed after completion",
+            Self::PendingWithoutYield => {
+                "generator function returned pending without yielding a value"
+            }
+        })
+    }
+}
+
+impl std::error::Error for Error {}
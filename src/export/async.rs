@@ -1,11 +1,14 @@
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures_core::Stream;
 
-use crate::detail::{GeneratorArg, GeneratorWrapper};
-use crate::{AsyncGenerator as _, GeneratorState, Resume};
+use crate::detail::{GeneratorArg, GeneratorInfo, GeneratorWrapper};
+use crate::{
+    AsyncGenerator as _, Error, GeneratorState, GeneratorStatus, Resume, YieldHint, YieldSite,
+};
 
 #[must_use = "generators are lazy and do nothing unless polled"]
 pub struct AsyncGenerator<F, Y, A> {
@@ -14,12 +17,46 @@ pub struct AsyncGenerator<F, Y, A> {
 }
 
 impl<F, Y, A> AsyncGenerator<F, Y, A> {
-    pub(crate) fn new(future: F) -> Self {
+    pub(crate) fn new(info: &'static GeneratorInfo, future: F) -> Self {
         Self {
-            inner: GeneratorWrapper::new(future),
+            inner: GeneratorWrapper::new(info, future),
             arg: GeneratorArg::Empty,
         }
     }
+
+    /// The current state of this generator.
+    pub fn status(&self) -> GeneratorStatus {
+        self.inner.status()
+    }
+
+    /// The yield site that this generator is suspended at.
+    ///
+    /// This is only known for generators declared with
+    /// `#[generator(introspect)]`, and only while the generator is suspended
+    /// after yielding a value. It is `None` otherwise.
+    pub fn current_yield_site(&self) -> Option<&'static YieldSite> {
+        self.inner.current_yield_site()
+    }
+
+    /// Every yield site of this generator, in the order that they appear in the
+    /// source.
+    ///
+    /// This is empty unless the generator was declared with
+    /// `#[generator(introspect)]`.
+    pub fn yield_sites(&self) -> &'static [YieldSite] {
+        self.inner.yield_sites()
+    }
+
+    pub(crate) fn with_size_hint(mut self, size_hint: (usize, Option<usize>)) -> Self {
+        self.inner.set_size_hint(size_hint);
+        self
+    }
+}
+
+impl<F, Y, A> fmt::Debug for AsyncGenerator<F, Y, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.debug("AsyncGenerator", f)
+    }
 }
 
 impl<F, Y, A> AsyncGenerator<F, Y, A>
@@ -29,6 +66,39 @@ where
     pub fn resume(self: Pin<&mut Self>, arg: A) -> Resume<'_, A, Self> {
         <Self as crate::AsyncGenerator<A>>::resume(self, arg)
     }
+
+    /// Resume this generator, returning an error instead of panicking if it
+    /// cannot be resumed.
+    ///
+    /// # Errors
+    /// - [`Error::Poisoned`] if a previous resume panicked.
+    /// - [`Error::Completed`] if the generator has already completed.
+    pub async fn try_resume(
+        mut self: Pin<&mut Self>,
+        arg: A,
+    ) -> Result<GeneratorState<Y, F::Output>, Error> {
+        let mut arg = Some(arg);
+        std::future::poll_fn(|cx| self.as_mut().try_poll_resume(cx, arg.take())).await
+    }
+
+    /// Poll this generator, returning an error instead of panicking if it
+    /// cannot be resumed.
+    ///
+    /// See [`try_resume`](Self::try_resume) for the errors this can return.
+    pub fn try_poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        arg: Option<A>,
+    ) -> Poll<Result<GeneratorState<Y, F::Output>, Error>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let wrapper = unsafe { Pin::new_unchecked(&mut this.inner) };
+
+        if let Some(arg) = arg {
+            this.arg = GeneratorArg::Arg(arg);
+        }
+
+        wrapper.try_poll_resume(cx, &mut this.arg)
+    }
 }
 
 impl<F, Y, A> crate::AsyncGenerator<A> for AsyncGenerator<F, Y, A>
@@ -56,6 +126,12 @@ where
 
 impl<F, Y, A> Unpin for AsyncGenerator<F, Y, A> where F: Unpin {}
 
+impl<F, Y, A> YieldHint for AsyncGenerator<F, Y, A> {
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
 impl<F, Y> Stream for AsyncGenerator<F, Y, ()>
 where
     F: Future<Output = ()>,
@@ -68,10 +144,15 @@ where
             GeneratorState::Complete(()) => None,
         })
     }
-}
 
-unsafe impl<F: Send, Y, A> Send for AsyncGenerator<F, Y, A> {}
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
 
-// SAFETY: We only expose &mut methods so the generator can never be accessed
-//         concurrently.
-unsafe impl<F: Send, Y, A> Sync for AsyncGenerator<F, Y, A> {}
+// SAFETY: The methods that take &self (`status`, `current_yield_site`,
+//         `yield_sites`, the size hints and the `Debug` impl) only read plain
+//         data about the generator and never access the future or the stored
+//         argument. Everything else requires &mut so the generator can never be
+//         accessed concurrently.
+unsafe impl<F: Send, Y: Send, A: Send> Sync for AsyncGenerator<F, Y, A> {}
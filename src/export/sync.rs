@@ -1,15 +1,68 @@
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 
-use crate::detail::GeneratorWrapper;
-use crate::{Generator, GeneratorState};
+use crate::detail::{GeneratorInfo, GeneratorWrapper};
+use crate::{Error, Generator, GeneratorState, GeneratorStatus, YieldHint, YieldSite};
 
 #[must_use = "generators are lazy and do nothing unless consumed"]
 pub struct SyncGenerator<F, Y, A>(GeneratorWrapper<F, Y, A>);
 
 impl<F, Y, A> SyncGenerator<F, Y, A> {
-    pub(crate) fn new(future: F) -> Self {
-        Self(GeneratorWrapper::new(future))
+    pub(crate) fn new(info: &'static GeneratorInfo, future: F) -> Self {
+        Self(GeneratorWrapper::new(info, future))
+    }
+
+    /// The current state of this generator.
+    pub fn status(&self) -> GeneratorStatus {
+        self.0.status()
+    }
+
+    /// The yield site that this generator is suspended at.
+    ///
+    /// This is only known for generators declared with
+    /// `#[generator(introspect)]`, and only while the generator is suspended
+    /// after yielding a value. It is `None` otherwise.
+    pub fn current_yield_site(&self) -> Option<&'static YieldSite> {
+        self.0.current_yield_site()
+    }
+
+    /// Every yield site of this generator, in the order that they appear in the
+    /// source.
+    ///
+    /// This is empty unless the generator was declared with
+    /// `#[generator(introspect)]`.
+    pub fn yield_sites(&self) -> &'static [YieldSite] {
+        self.0.yield_sites()
+    }
+
+    pub(crate) fn with_size_hint(mut self, size_hint: (usize, Option<usize>)) -> Self {
+        self.0.set_size_hint(size_hint);
+        self
+    }
+}
+
+impl<F, Y, A> SyncGenerator<F, Y, A>
+where
+    F: Future,
+{
+    /// Resume this generator, returning an error instead of panicking if it
+    /// cannot be resumed.
+    ///
+    /// # Errors
+    /// - [`Error::Poisoned`] if a previous resume panicked.
+    /// - [`Error::Completed`] if the generator has already completed.
+    /// - [`Error::PendingWithoutYield`] if the generator awaited on a future
+    ///   other than a yield. The generator cannot be resumed after this.
+    pub fn try_resume(self: Pin<&mut Self>, arg: A) -> Result<GeneratorState<Y, F::Output>, Error> {
+        let wrapper = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+        wrapper.try_resume(arg)
+    }
+}
+
+impl<F, Y, A> fmt::Debug for SyncGenerator<F, Y, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.debug("SyncGenerator", f)
     }
 }
 
@@ -28,6 +81,18 @@ where
 
 impl<F, Y, A> Unpin for SyncGenerator<F, Y, A> where F: Unpin {}
 
+impl<F, Y, A> YieldHint for SyncGenerator<F, Y, A> {
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+// SAFETY: The methods that take &self (`status`, `current_yield_site`,
+//         `yield_sites`, the size hints and the `Debug` impl) only read plain
+//         data about the generator and never access the future. Everything else
+//         requires &mut so the generator can never be accessed concurrently.
+unsafe impl<F: Send, Y, A> Sync for SyncGenerator<F, Y, A> {}
+
 impl<F, Y> Iterator for SyncGenerator<F, Y, ()>
 where
     F: Future<Output = ()>,
@@ -38,6 +103,17 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         Pin::new(self).next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn fold<B, G>(mut self, init: B, func: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        Pin::new(&mut self).fold(init, func)
+    }
 }
 
 impl<F, Y> Iterator for Pin<&mut SyncGenerator<F, Y, ()>>
@@ -52,6 +128,22 @@ where
             GeneratorState::Complete(()) => None,
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    // Most consuming iterator methods (for_each, sum, count, etc.) are implemented
+    // in terms of fold, so this lets them all resume the generator without
+    // rebuilding the context for every value. try_fold can't be specialized on
+    // stable since the Try trait is unstable.
+    fn fold<B, G>(self, init: B, func: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        let wrapper = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+        wrapper.fold(init, func).0
+    }
 }
 
 impl<F, Y> Iterator for Pin<Box<SyncGenerator<F, Y, ()>>>
@@ -63,4 +155,15 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         self.as_mut().next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn fold<B, G>(mut self, init: B, func: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        self.as_mut().fold(init, func)
+    }
 }
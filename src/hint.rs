@@ -0,0 +1,80 @@
+use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+
+/// Generators that can report how many values they have left to yield.
+///
+/// This is kept separate from [`Generator`](crate::Generator) so that it
+/// stays the same as the unstable one in [`std::ops`]. It is implemented by
+/// the generators created by the fauxgen macros and by the wrappers in this
+/// crate. [`GeneratorIter`](crate::GeneratorIter) and the other wrappers use
+/// it to implement `size_hint`, so generators used with them need to
+/// implement it too. Generators that can't tell how many values they have
+/// left can use the default method:
+///
+/// ```
+/// # use std::pin::Pin;
+/// # use fauxgen::{Generator, GeneratorState};
+/// struct Forever;
+///
+/// impl Generator for Forever {
+///     type Yield = u32;
+///     type Return = ();
+///
+///     fn resume(self: Pin<&mut Self>, _: ()) -> GeneratorState<u32, ()> {
+///         GeneratorState::Yielded(0)
+///     }
+/// }
+///
+/// impl fauxgen::YieldHint for Forever {}
+///
+/// let iter = fauxgen::GeneratorIter::new(Forever);
+/// assert_eq!(iter.size_hint(), (0, None));
+/// ```
+pub trait YieldHint {
+    /// Returns the bounds on the number of values this generator has left to
+    /// yield.
+    ///
+    /// This works the same way as [`Iterator::size_hint`]. The default
+    /// returns `(0, None)`, which is correct for any generator.
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<G> YieldHint for &mut G
+where
+    G: YieldHint + ?Sized,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        G::yield_hint(self)
+    }
+}
+
+impl<G> YieldHint for Box<G>
+where
+    G: YieldHint + ?Sized,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        G::yield_hint(self)
+    }
+}
+
+impl<P> YieldHint for Pin<P>
+where
+    P: Deref,
+    P::Target: YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        P::Target::yield_hint(self)
+    }
+}
+
+impl<G> YieldHint for AssertUnwindSafe<G>
+where
+    G: YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.0.yield_hint()
+    }
+}
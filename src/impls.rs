@@ -3,7 +3,7 @@ use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use crate::{AsyncGenerator, GeneratorState};
+use crate::{AsyncGenerator, Generator, GeneratorState};
 
 impl<A, G> AsyncGenerator<A> for &mut G
 where
@@ -38,6 +38,19 @@ where
     }
 }
 
+impl<A, G> Generator<A> for AssertUnwindSafe<G>
+where
+    G: Generator<A>,
+{
+    type Yield = G::Yield;
+    type Return = G::Return;
+
+    fn resume(self: Pin<&mut Self>, arg: A) -> GeneratorState<Self::Yield, Self::Return> {
+        let gen = unsafe { self.map_unchecked_mut(|this| &mut **this) };
+        gen.resume(arg)
+    }
+}
+
 impl<A, G> AsyncGenerator<A> for Box<G>
 where
     G: AsyncGenerator<A>,
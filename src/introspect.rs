@@ -0,0 +1,162 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// A place within a generator where it can be suspended.
+///
+/// Generators declared with `#[generator(introspect)]` number each of their
+/// `yield` expressions (along with `r#yield!` and `yield_batch!`) and keep a
+/// table of where they are. The site that a generator is currently suspended
+/// at is returned by `current_yield_site` on the generator types, and is
+/// included in their `Debug` output.
+///
+/// With `introspect(counters)` each site also counts how many times the
+/// generator has been resumed from it, and how long it spent suspended there.
+/// The table is shared by every instance of the generator, so the counters
+/// cover all of them. This includes the instances of a generic generator
+/// with different type arguments, whose counts are merged together.
+pub struct YieldSite {
+    index: usize,
+    file: &'static str,
+    line: u32,
+    column: u32,
+    counters: Option<SiteCounters>,
+}
+
+struct SiteCounters {
+    resumes: AtomicU64,
+    suspended_nanos: AtomicU64,
+}
+
+impl YieldSite {
+    #[doc(hidden)]
+    pub const fn new(
+        index: usize,
+        file: &'static str,
+        line: u32,
+        column: u32,
+        counters: bool,
+    ) -> Self {
+        Self {
+            index,
+            file,
+            line,
+            column,
+            counters: match counters {
+                true => Some(SiteCounters {
+                    resumes: AtomicU64::new(0),
+                    suspended_nanos: AtomicU64::new(0),
+                }),
+                false => None,
+            },
+        }
+    }
+
+    /// The position of this site within the generator, counting from zero in
+    /// the order that the sites appear in the source.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The file containing this site.
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// The line of this site within its file.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The column of this site within its line.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// The number of times a generator has been resumed after yielding from
+    /// this site, or `None` if the generator doesn't keep counters.
+    pub fn resumes(&self) -> Option<u64> {
+        let counters = self.counters.as_ref()?;
+        Some(counters.resumes.load(Ordering::Relaxed))
+    }
+
+    /// The total time that generators spent suspended at this site before
+    /// being resumed, or `None` if the generator doesn't keep counters.
+    ///
+    /// This only includes suspensions that have ended.
+    pub fn time_suspended(&self) -> Option<Duration> {
+        let counters = self.counters.as_ref()?;
+        Some(Duration::from_nanos(
+            counters.suspended_nanos.load(Ordering::Relaxed),
+        ))
+    }
+
+    /// Start timing a suspension at this site, if it keeps counters.
+    #[inline]
+    pub(crate) fn suspend(&self) -> Option<Instant> {
+        self.counters.as_ref().map(|_| Instant::now())
+    }
+
+    /// Record that a generator was resumed after being suspended at this site
+    /// since `since`.
+    pub(crate) fn resume(&self, since: Instant) {
+        if let Some(counters) = &self.counters {
+            let nanos = u64::try_from(since.elapsed().as_nanos()).unwrap_or(u64::MAX);
+
+            counters.resumes.fetch_add(1, Ordering::Relaxed);
+            counters.suspended_nanos.fetch_add(nanos, Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Display for YieldSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl fmt::Debug for YieldSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("YieldSite");
+        f.field("index", &self.index)
+            .field("location", &format_args!("{self}"));
+
+        if let (Some(resumes), Some(time)) = (self.resumes(), self.time_suspended()) {
+            f.field("resumes", &resumes).field("time_suspended", &time);
+        }
+
+        f.finish()
+    }
+}
+
+/// Tracks the site of a single yield, for the futures returned by the token.
+#[derive(Default)]
+pub(crate) struct SiteTracker {
+    site: Option<&'static YieldSite>,
+    since: Option<Instant>,
+}
+
+impl SiteTracker {
+    pub fn set(&mut self, site: &'static YieldSite) {
+        self.site = Some(site);
+    }
+
+    /// Called when the yield hands its value over to the generator, with the
+    /// site slot of the generator's state.
+    #[inline]
+    pub fn suspend(&mut self, current: &mut Option<&'static YieldSite>) {
+        *current = self.site;
+
+        if let Some(site) = self.site {
+            self.since = site.suspend();
+        }
+    }
+
+    /// Called when the generator has been resumed after the yield.
+    #[inline]
+    pub fn resume(&mut self) {
+        if let (Some(site), Some(since)) = (self.site, self.since.take()) {
+            site.resume(since);
+        }
+    }
+}
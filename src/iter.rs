@@ -1,6 +1,6 @@
 use std::pin::Pin;
 
-use crate::{Generator, GeneratorState};
+use crate::{Generator, GeneratorState, YieldHint};
 
 #[cfg(feature = "macros")]
 use crate::generator;
@@ -13,6 +13,7 @@ used_in_docs!(generator);
 /// The generators created by the [`generator`] macro implement [`Iterator`]
 /// once they are pinned. For other implementations of [`Generator`], though,
 /// you can use `GeneratorIter` to convert them into an iterator.
+#[derive(Debug)]
 pub struct GeneratorIter<G>(G);
 
 impl<G> GeneratorIter<G> {
@@ -27,7 +28,7 @@ impl<G> GeneratorIter<G> {
 
 impl<G> Iterator for GeneratorIter<G>
 where
-    G: Generator<(), Return = ()> + Unpin,
+    G: Generator<(), Return = ()> + YieldHint + Unpin,
 {
     type Item = G::Yield;
 
@@ -37,4 +38,112 @@ where
             GeneratorState::Yielded(value) => Some(value),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.yield_hint()
+    }
+}
+
+/// Wrapper around a generator that yields values and returns a result.
+///
+/// This is the [`Iterator`] counterpart to
+/// [`GeneratorTryStream`](crate::GeneratorTryStream). All the yielded values
+/// become `Ok(v)` items and returning an error emits a final `Err(e)` item
+/// before the iterator completes. The generators declared with
+/// `#[generator(try)]` return this type.
+///
+/// Like the generators created by the [`generator`] macro, this implements
+/// [`Iterator`] once it is pinned, or directly if the generator is [`Unpin`].
+///
+/// # Example
+/// ```
+/// use fauxgen::GeneratorTryIter;
+///
+/// #[fauxgen::generator(yield = i32)]
+/// fn numbers() -> Result<(), &'static str> {
+///     r#yield!(44);
+///     r#yield!(88);
+///     Err("ran out of numbers")
+/// }
+///
+/// let iter = std::pin::pin!(GeneratorTryIter::new(numbers()));
+/// let items: Vec<_> = iter.collect();
+/// assert_eq!(items, [Ok(44), Ok(88), Err("ran out of numbers")]);
+/// ```
+#[derive(Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct GeneratorTryIter<G> {
+    gen: G,
+    done: bool,
+}
+
+impl<G> GeneratorTryIter<G> {
+    /// Create an iterator from an existing generator.
+    pub fn new(gen: G) -> Self {
+        Self { gen, done: false }
+    }
+
+    /// Convert this iterator back into the generator.
+    pub fn into_inner(self) -> G {
+        self.gen
+    }
+
+    fn hint(&self) -> (usize, Option<usize>)
+    where
+        G: YieldHint,
+    {
+        if self.done {
+            return (0, Some(0));
+        }
+
+        // Returning an error adds one more item to the end of the iterator.
+        let (lower, upper) = self.gen.yield_hint();
+        (lower, upper.and_then(|upper| upper.checked_add(1)))
+    }
+}
+
+impl<G, E> Iterator for Pin<&mut GeneratorTryIter<G>>
+where
+    G: Generator<(), Return = Result<(), E>> + YieldHint,
+{
+    type Item = Result<G::Yield, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: This is just pin projection so it is safe.
+        let (gen, done) = unsafe {
+            let this = self.as_mut().get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.gen), &mut this.done)
+        };
+
+        if *done {
+            return None;
+        }
+
+        match gen.resume(()) {
+            GeneratorState::Yielded(item) => Some(Ok(item)),
+            GeneratorState::Complete(result) => {
+                *done = true;
+                result.err().map(Err)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint()
+    }
+}
+
+impl<G, E> Iterator for GeneratorTryIter<G>
+where
+    G: Generator<(), Return = Result<(), E>> + YieldHint + Unpin,
+{
+    type Item = Result<G::Yield, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Pin::new(self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint()
+    }
 }
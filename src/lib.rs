@@ -132,6 +132,29 @@
 //!
 //! Note that using the `argument!` macro after you have called `yield!` is
 //! likely to result in a panic.
+//!
+//! ## Yielding from concurrent futures
+//! Yields also work within futures run concurrently by combinators such as
+//! `join!`, `select!` or `FuturesUnordered`. Only one value is yielded at a
+//! time: the first yield to be polled goes first and any others wait until it
+//! has received its argument.
+//!
+//! ```
+//! # use std::pin::pin;
+//! # use fauxgen::GeneratorToken;
+//! let gen = fauxgen::gen!(|token: GeneratorToken<u32>| {
+//!     futures_util::join!(
+//!         async {
+//!             token.yield_(1).await;
+//!             token.yield_(2).await;
+//!         },
+//!         token.yield_(3),
+//!     );
+//! });
+//!
+//! let values: Vec<_> = pin!(gen).collect();
+//! assert_eq!(values, [1, 2, 3]);
+//! ```
 
 #![cfg_attr(std_generators, feature(generator_trait))]
 
@@ -152,12 +175,24 @@ macro_rules! used_in_docs {
 
 #[path = "async.rs"]
 mod asynk;
+mod boxed;
 mod detail;
+#[cfg(feature = "serde")]
+mod durable;
+mod error;
 mod export;
+mod hint;
 mod impls;
+mod introspect;
 mod iter;
+mod recursive;
+mod replay;
+mod share;
+mod status;
 mod stream;
+mod tee;
 mod token;
+mod unwind;
 
 #[cfg(not(std_generators))]
 mod core;
@@ -183,6 +218,46 @@ mod readme {}
 ///   within the generator.
 /// - `crate` - A path at which the fauxgen crate can be accessed. If not
 ///   specified then it will use `::fauxgen`.
+/// - `recursive` - Turn this generator into a [`Recursive`] generator. The
+///   function will return a [`Recursive`] instead of an anonymous generator
+///   type and the `recurse!` macro becomes available within its body.
+///   Recursive generators cannot be async and cannot take an argument.
+/// - `size_hint` - An expression evaluating to the initial `(usize,
+///   Option<usize>)` bounds on the number of values the generator will yield.
+///   This is what [`Iterator::size_hint`] returns before the generator is
+///   first resumed. It is evaluated when the generator is created and can
+///   refer to the function parameters.
+/// - `try` - Make the generator yield results. The function must return
+///   `Result<(), E>`, so `?` can be used within it. Sync generators return a
+///   [`GeneratorTryIter`] and async generators return a
+///   [`GeneratorTryStream`]. Each yielded value becomes an `Ok` item and
+///   returning an error emits a final `Err` item. `try` generators cannot take
+///   an argument.
+/// - `max_size` - A constant expression giving the maximum size, in bytes,
+///   of the generator. Anything that is held across a yield is stored within
+///   the generator, so large arrays can easily result in a generator that
+///   overflows the stack. Exceeding the limit results in a compile error. As
+///   this is only checked once the generator's type is known, it is not
+///   reported by `cargo check`.
+/// - `boxed_state` - Store the state of the generator on the heap. This keeps
+///   the generator itself small no matter what it holds across a yield, at the
+///   cost of an allocation when it is created. It also makes the generator
+///   [`Unpin`]. Along with `max_size`, the state is only moved to the heap if
+///   it would not fit within `max_size`, and the generator is never larger
+///   than `max_size` (not counting the slot for the resume argument of an
+///   async generator). Since the state may then be stored inline, the
+///   generator is no longer [`Unpin`].
+/// - `maybe_async` - Declare both a sync and an async version of the
+///   generator from the same body. See [`maybe_async`](#maybe_async).
+/// - `introspect` - Record where each `yield` within the generator is, so
+///   that `current_yield_site` on the generator can report where it is
+///   suspended. With `introspect(counters)` each [`YieldSite`] also counts how
+///   often it was resumed from and how long it spent suspended. The counts
+///   are shared by every instance of the generator, including those of a
+///   generic generator with different type arguments. `recursive` generators
+///   cannot be introspected.
+/// - `allow(...)` - Turn off some of the checks on the generator body. See
+///   [Checks](#checks).
 ///
 /// # Interface
 /// This attribute macro creates two regular macros that can only be used inside
@@ -221,6 +296,97 @@ mod readme {}
 /// }
 /// ```
 ///
+/// Alternatively, a parameter of the function can be marked with `#[arg]`.
+/// It is removed from the function signature and is instead bound to the
+/// first argument when the generator starts. If the `arg` parameter is not
+/// specified then the type of this parameter is used as the argument type.
+///
+/// ```
+/// #[fauxgen::generator]
+/// fn example(#[arg] first: &'static str) {
+///     let second = r#yield!();
+///     let third = r#yield!();
+/// }
+/// ```
+///
+/// ## `size_hint!`
+/// This macro takes a lower and an upper bound on the number of values the
+/// generator has left to yield and uses them to update the generator's size
+/// hint. The hint is reduced by one each time the generator yields so it only
+/// needs to be updated when the estimate changes. It is not available within
+/// `recursive` generators.
+///
+/// ```
+/// #[fauxgen::generator(yield = u32, size_hint = (0, Some(count)))]
+/// fn evens(count: usize) {
+///     let values: Vec<u32> = (0..count as u32).filter(|v| v % 2 == 0).collect();
+///     size_hint!(values.len(), Some(values.len()));
+///
+///     for value in values {
+///         r#yield!(value);
+///     }
+/// }
+///
+/// let mut gen = std::pin::pin!(evens(5));
+/// assert_eq!(gen.size_hint(), (0, Some(5)));
+/// assert_eq!(gen.next(), Some(0));
+/// assert_eq!(gen.size_hint(), (2, Some(2)));
+/// ```
+///
+/// ## `yield_batch!`
+/// This macro takes anything that implements [`IntoIterator`] and yields each
+/// of its values while only suspending the generator once. When the generator
+/// is consumed as an [`Iterator`] or a [`Stream`](futures_core::Stream) the
+/// values are taken straight from the iterator, which avoids the cost of
+/// resuming the generator for every value. Resuming the generator through
+/// [`Generator::resume`] still returns each value separately.
+///
+/// This is only available for generators that don't take an argument, and
+/// not within `recursive` generators.
+///
+/// ```
+/// #[fauxgen::generator(yield = u32)]
+/// fn chunks() {
+///     for start in [0, 100, 200] {
+///         yield_batch!(start..start + 100);
+///     }
+/// }
+///
+/// assert_eq!(std::pin::pin!(chunks()).count(), 300);
+/// ```
+///
+/// ## `recurse!`
+/// This macro is only available within `recursive` generators. It takes
+/// another [`Recursive`] generator, yields all of its values, and then
+/// evaluates to the value it returned. See [`Recursive`] for an example.
+///
+/// ## `yield_from_boxed!`
+/// This macro is only available within `recursive` generators. It works like
+/// `recurse!` but takes any generator with the same yield and return types,
+/// which is boxed using [`Recursive::boxed`]. This is how a recursive
+/// generator can yield the values of a generator that isn't recursive itself.
+///
+/// ```
+/// #[fauxgen::generator(yield = u32)]
+/// fn leaf(value: u32) {
+///     r#yield!(value);
+///     r#yield!(value + 1);
+/// }
+///
+/// #[fauxgen::generator(yield = u32, recursive)]
+/// fn tree(depth: u32) {
+///     if depth == 0 {
+///         return yield_from_boxed!(leaf(100));
+///     }
+///
+///     r#yield!(depth);
+///     recurse!(tree(depth - 1));
+/// }
+///
+/// let values: Vec<_> = tree(2).collect();
+/// assert_eq!(values, [2, 1, 100, 101]);
+/// ```
+///
 /// # Using the `yield` keyword
 /// This macro supports using the `yield` keyword in place of the `r#yield!`
 /// macro. Note that the keyword itself is unstable in rust and to just use it
@@ -236,14 +402,344 @@ mod readme {}
 ///     yield "third";
 /// }
 /// ```
+///
+/// `yield` expressions are also expanded within the arguments of macros that
+/// take a list of expressions (such as `vec!`, `format!` or `assert_eq!`),
+/// `vec![value; count]` and `matches!`. A `yield` within the arguments of any
+/// other macro can't be expanded and results in a warning.
+///
+/// # `maybe_async`
+/// Generators declared with `maybe_async` expand to two functions: a sync
+/// generator with the same name, and an async generator with `_async` appended
+/// to the name. The body is written as if it were async. In the sync version
+/// an awaited call to a function or method whose name ends in `_async` calls
+/// the one without the suffix instead, and the `.await` is removed. This is
+/// the same naming convention as the generators themselves use.
+///
+/// Any other `.await`, along with async blocks and closures, can't be made
+/// sync and results in a compile error.
+///
+/// ```
+/// struct Reader(Vec<u8>);
+///
+/// impl Reader {
+///     fn read(&mut self) -> Option<u8> {
+///         self.0.pop()
+///     }
+///
+///     async fn read_async(&mut self) -> Option<u8> {
+///         self.0.pop()
+///     }
+/// }
+///
+/// #[fauxgen::generator(yield = u8, maybe_async)]
+/// fn bytes(mut reader: Reader) {
+///     while let Some(byte) = reader.read_async().await {
+///         r#yield!(byte);
+///     }
+/// }
+///
+/// let sync_bytes: Vec<u8> = std::pin::pin!(bytes(Reader(vec![1, 2]))).collect();
+/// assert_eq!(sync_bytes, [2, 1]);
+///
+/// // An async generator that implements `Stream`.
+/// let async_bytes = bytes_async(Reader(vec![1, 2]));
+/// ```
+///
+/// # Checks
+/// Some mistakes within a generator body only show up as a panic when the
+/// generator runs. The macro looks for the common ones and reports them at
+/// compile time instead:
+/// - `foreign_await` - A warning for an `.await` within a generator that
+///   isn't async. Sync generators can't be woken up, so they panic if any
+///   future other than the ones created by the macros above is not
+///   immediately ready. Awaiting a future that is always ready works fine.
+/// - `late_argument` - An error for `argument!()` after the generator may
+///   already have yielded. The first argument is gone by then, so this always
+///   panics.
+///
+/// These checks only look at the syntax of the body. If one of them gets it
+/// wrong it can be turned off by listing it in `allow(...)`.
+///
+/// ```
+/// #[fauxgen::generator(yield = u32, allow(foreign_await))]
+/// fn ready() {
+///     let value = std::future::ready(1).await;
+///     r#yield!(value);
+/// }
+///
+/// assert_eq!(std::pin::pin!(ready()).collect::<Vec<_>>(), [1]);
+/// ```
+///
+/// ```compile_fail
+/// #[fauxgen::generator(yield = u32, arg = u32)]
+/// fn late() {
+///     r#yield!(1);
+///     let _ = argument!();
+/// }
+/// ```
 #[cfg(feature = "macros")]
 pub use fauxgen_macros::generator;
 
+/// Declare a generator closure.
+///
+/// Attributes on closure expressions are not yet stable, so this macro takes
+/// a closure annotated with [`#[generator(...)]`](generator) instead. It
+/// accepts the same parameters as the [`generator`] macro, except for
+/// `recursive`, and the body can use the same macros (and the `yield`
+/// keyword). Calling the closure returns a new generator.
+///
+/// ```
+/// use std::pin::pin;
+///
+/// let step = 3;
+/// let multiples = fauxgen::closure!(#[generator(yield = u32)] |count: u32| {
+///     for i in 0..count {
+///         r#yield!(i * step);
+///     }
+/// });
+///
+/// let values: Vec<_> = pin!(multiples(4)).collect();
+/// assert_eq!(values, [0, 3, 6, 9]);
+/// ```
+///
+/// Prefixing the closure with `async` creates an async generator instead.
+/// The generator owns everything it uses from the closure, so the variables
+/// the closure captures are moved into each generator it creates. Only
+/// closures that capture `Copy` values (or nothing at all) can be called more
+/// than once.
+///
+/// ```
+/// # use std::pin::pin;
+/// # use fauxgen::{Generator, GeneratorState};
+/// let echo = fauxgen::closure!(#[generator(yield = String, arg = u32)] || -> u32 {
+///     let mut value = argument!();
+///     while value != 0 {
+///         value = r#yield!(value.to_string());
+///     }
+///     value
+/// });
+///
+/// let mut gen = pin!(echo());
+/// assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded("1".into()));
+/// assert_eq!(gen.as_mut().resume(0), GeneratorState::Complete(0));
+/// ```
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::closure;
+
+/// Declare an inline generator.
+///
+/// This is a procedural alternative to [`gen!`] that evaluates to a generator
+/// directly. Instead of a [`GeneratorToken`], the body has access to the same
+/// macros as a [`generator`] function and can use the `yield` keyword.
+///
+/// The macro starts with the parameters of the generator, followed by a `;`
+/// and then the body. The parameters are the same as those of the
+/// [`generator`] macro, except for `recursive`. They can be preceded by
+/// `async` to create an async generator, and followed by `-> Type` to declare
+/// the return type. The body always owns the variables it uses, so `move` is
+/// accepted but not required.
+///
+/// ```
+/// use std::pin::pin;
+/// use fauxgen::{Generator, GeneratorState};
+///
+/// let limit = 3;
+/// let gen = fauxgen::gen_block! { yield = u32, arg = u32 -> u32;
+///     let mut total = 0;
+///     for i in 0..limit {
+///         total += r#yield!(i);
+///     }
+///     total
+/// };
+///
+/// let mut gen = pin!(gen);
+/// assert_eq!(gen.as_mut().resume(0), GeneratorState::Yielded(0));
+/// assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded(1));
+/// assert_eq!(gen.as_mut().resume(2), GeneratorState::Yielded(2));
+/// assert_eq!(gen.as_mut().resume(3), GeneratorState::Complete(6));
+/// ```
+///
+/// Unlike `gen`, `gen_block` is not a reserved keyword in the 2024 edition.
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::gen_block;
+
+/// Declare an inline generator that yields results.
+///
+/// This takes the same input as [`gen_block!`], without `async`, and declares
+/// a [`try` generator](generator). The body evaluates to `()` and `?` can be
+/// used within it to end the generator with an error. The generator is
+/// wrapped in a [`GeneratorTryIter`], so it can be used as an iterator of
+/// results once pinned.
+///
+/// The error type often can't be inferred from the uses of `?` alone. It can
+/// be given by writing the return type as `-> Result<(), E>`.
+///
+/// ```
+/// use std::num::ParseIntError;
+/// use std::pin::pin;
+///
+/// let words = ["1", "2", "three"];
+/// let numbers = fauxgen::try_gen! { yield = u32 -> Result<(), ParseIntError>;
+///     for word in words {
+///         r#yield!(word.parse()?);
+///     }
+/// };
+///
+/// let numbers: Vec<_> = pin!(numbers).collect();
+/// assert_eq!(numbers[..2], [Ok(1), Ok(2)]);
+/// assert!(numbers[2].is_err());
+/// ```
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::try_gen;
+
+/// Declare an inline async generator that yields results.
+///
+/// This is the async version of [`try_gen!`]. The generator is wrapped in a
+/// [`GeneratorTryStream`], so it can be used as a stream of results.
+///
+/// ```
+/// use std::num::ParseIntError;
+///
+/// use futures_util::StreamExt;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let words = ["1", "2", "three"];
+/// let numbers = fauxgen::try_stream! { yield = u32 -> Result<(), ParseIntError>;
+///     for word in words {
+///         tokio::task::yield_now().await;
+///         r#yield!(word.parse()?);
+///     }
+/// };
+///
+/// let numbers: Vec<_> = numbers.collect().await;
+/// assert_eq!(numbers[..2], [Ok(1), Ok(2)]);
+/// assert!(numbers[2].is_err());
+/// # }
+/// ```
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::try_stream;
+
+/// Implement a method of an impl block, such as [`IntoIterator::into_iter`],
+/// with the body of a generator.
+///
+/// The impl block must contain exactly one method, which is written like a
+/// [`generator`] function without a return type. It takes the same
+/// parameters as the [`generator`] macro, except for `arg`, `recursive`,
+/// `try`, `maybe_async`, `max_size` and `boxed_state`.
+///
+/// The type of a generator can't be named on stable, so the method returns a
+/// [`BoxedIter`] instead, which stores the state of the generator on the
+/// heap. Within a trait impl, the `Item` and `IntoIter` associated types are
+/// declared as well (unless the impl already declares them). The boxed state
+/// is bounded by the lifetime parameter of the impl, or `'static` if it has
+/// none. Impls with more than one lifetime parameter are not supported.
+///
+/// [`BoxedIter`] is never [`Send`]. Adding `send` to the parameters returns a
+/// [`BoxedSendIter`] instead, which requires the body to be [`Send`].
+///
+/// ```
+/// struct Tree {
+///     values: Vec<u32>,
+///     children: Vec<Tree>,
+/// }
+///
+/// #[fauxgen::into_iter(yield = &'a u32)]
+/// impl<'a> IntoIterator for &'a Tree {
+///     fn into_iter(self) {
+///         yield_batch!(&self.values);
+///         for child in &self.children {
+///             for value in child {
+///                 r#yield!(value);
+///             }
+///         }
+///     }
+/// }
+///
+/// let leaf = |value| Tree { values: vec![value], children: vec![] };
+/// let tree = Tree {
+///     values: vec![1, 2],
+///     children: vec![leaf(3), leaf(4)],
+/// };
+///
+/// let values: Vec<_> = tree.into_iter().copied().collect();
+/// assert_eq!(values, [1, 2, 3, 4]);
+/// ```
+///
+/// Within an inherent impl only the method is changed. It returns a
+/// `BoxedIter<'_, Y>`, so the generator can borrow from `self`.
+///
+/// ```
+/// # struct Tree { values: Vec<u32> }
+/// #[fauxgen::into_iter(yield = u32)]
+/// impl Tree {
+///     fn doubled(&self) {
+///         for value in &self.values {
+///             r#yield!(value * 2);
+///         }
+///     }
+/// }
+///
+/// let tree = Tree { values: vec![1, 2] };
+/// assert_eq!(tree.doubled().collect::<Vec<_>>(), [2, 4]);
+/// ```
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::into_iter;
+
+/// Implement a method of an impl block with the body of an async generator.
+///
+/// This is the async version of [`into_iter`]. The method returns a
+/// [`BoxedStream`] and, within a trait impl, the associated types declared
+/// are `Item` and `IntoStream`. The method may be declared as `async fn`,
+/// though it returns the stream without being awaited either way.
+///
+/// As with [`into_iter`], `send` returns a [`BoxedSendStream`] instead, which
+/// can be spawned onto a multi-threaded runtime.
+///
+/// ```
+/// use futures_util::StreamExt;
+///
+/// struct Feed {
+///     pages: Vec<&'static str>,
+/// }
+///
+/// #[fauxgen::into_stream(yield = String)]
+/// impl Feed {
+///     async fn fetch(&self) {
+///         for page in &self.pages {
+///             tokio::task::yield_now().await;
+///             r#yield!(page.to_uppercase());
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let feed = Feed { pages: vec!["a", "b"] };
+/// assert_eq!(feed.fetch().collect::<Vec<_>>().await, ["A", "B"]);
+/// # }
+/// ```
+#[cfg(feature = "macros")]
+pub use fauxgen_macros::into_stream;
+
 pub use crate::asynk::{AsyncGenerator, Resume};
+pub use crate::boxed::{BoxedIter, BoxedSendIter, BoxedSendStream, BoxedStream};
 pub use crate::core::{Generator, GeneratorState};
-pub use crate::iter::GeneratorIter;
+#[cfg(feature = "serde")]
+pub use crate::durable::Durable;
+pub use crate::error::Error;
+pub use crate::hint::YieldHint;
+pub use crate::introspect::YieldSite;
+pub use crate::iter::{GeneratorIter, GeneratorTryIter};
+pub use crate::recursive::Recursive;
+pub use crate::replay::{ReplayValue, Replayable};
+pub use crate::share::{share, LagPolicy, Lagged, Share};
+pub use crate::status::GeneratorStatus;
 pub use crate::stream::{GeneratorStream, GeneratorTryStream};
+pub use crate::tee::{tee, Tee};
 pub use crate::token::GeneratorToken;
+pub use crate::unwind::CatchUnwind;
 
 /// Declare an inline generator function.
 ///
@@ -253,6 +749,8 @@ pub use crate::token::GeneratorToken;
 /// Unlike with the [`generator`] macro, this generator type instead takes in a
 /// [`GeneratorToken`] which is used to yield values and to access generator
 /// arguments.
+/// See [`gen_block!`] for an inline generator that can use the same macros as
+/// the [`generator`] macro instead.
 ///
 /// # Example
 /// The simplest type of generator is one which only yields values:
@@ -279,6 +777,26 @@ pub use crate::token::GeneratorToken;
 /// See the documentation of the [`generator`] macro for a description of what
 /// each one does.
 ///
+/// The token is stored within the generator itself, so creating an inline
+/// generator does not allocate.
+///
+/// The closure can take a second parameter, which is bound to the argument
+/// passed to the first `resume` call. This saves having to call
+/// [`GeneratorToken::argument`] before the first yield.
+///
+/// ```
+/// use fauxgen::{gen, Generator, GeneratorState};
+///
+/// let gen = gen!(|token, first: u32| {
+///     let second = token.yield_(first).await;
+///     first + second
+/// });
+/// let mut gen = std::pin::pin!(gen);
+///
+/// assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded(1));
+/// assert_eq!(gen.as_mut().resume(2), GeneratorState::Complete(3));
+/// ```
+///
 /// # Restrictions
 /// This macro allows you to use await within the generator. However, it is an
 /// error to do this unless the generator is async. Awaiting on a future other
@@ -287,34 +805,43 @@ pub use crate::token::GeneratorToken;
 #[cfg(doc)]
 #[macro_export]
 macro_rules! gen {
-    (async $(move)? $func:expr) => {};
-    (      $(move)? $func:expr) => {};
+    (async $(move)? |$token:ident $(, $arg:ident)?| $body:expr) => {};
+    (      $(move)? |$token:ident $(, $arg:ident)?| $body:expr) => {};
 }
 
 /// Declare an inline generator function.
 #[cfg(not(doc))]
 #[macro_export]
 macro_rules! gen {
-    (async $(move $($dummy:tt)?)? |$token:ident$( : $ty:ty)?| $body:expr) => {{
-        let func = $(move $($dummy)?)? |$token $( : $ty)?| async move { $body };
-        $crate::gen_impl!(gen_async => func)
-    }};
-    ($(move $($dummy:tt)?)? |$token:ident$( : $ty:ty)?| $body:expr) => {{
-        let func = $(move $($dummy)?)? |$token $( : $ty)?| async move { $body };
-        $crate::gen_impl!(gen_sync => func)
-    }};
+    // The body always ends up within an `async move` block, so variables it uses
+    // are moved into the generator whether or not `move` is present.
+    (async $(move)? |$token:ident$( : $ty:ty)? $(, $arg:ident$( : $arg_ty:ty)?)?| $body:expr) => {
+        $crate::gen_impl!(gen_async => |$token $( : $ty)? $(, $arg $( : $arg_ty)?)?| $body)
+    };
+    ($(move)? |$token:ident$( : $ty:ty)? $(, $arg:ident$( : $arg_ty:ty)?)?| $body:expr) => {
+        $crate::gen_impl!(gen_sync => |$token $( : $ty)? $(, $arg $( : $arg_ty)?)?| $body)
+    };
 }
 
 #[macro_export]
 #[doc(hidden)]
 macro_rules! gen_impl {
-    ($genfn:ident => $func:expr) => {{
-        let token = $crate::__private::token();
+    ($genfn:ident => |$token:ident$( : $ty:ty)? $(, $arg:ident$( : $arg_ty:ty)?)?| $body:expr) => {{
+        const INFO: $crate::__private::GeneratorInfo = $crate::__private::GeneratorInfo {
+            name: "gen!",
+            file: file!(),
+            line: line!(),
+            column: column!(),
+            sites: &[],
+        };
+
+        let token = $crate::__private::token(&INFO);
 
-        $crate::__private::$genfn(token.marker(), async move {
-            let token: $crate::GeneratorToken<_, _> =
-                $crate::__private::register_owned(token).await;
-            $func(token).await
+        $crate::__private::$genfn(token.marker(), &INFO, async move {
+            let $token $( : $ty)? = $crate::__private::register_token(token).await;
+            $( let $arg $( : $arg_ty)? = $token.argument().await; )?
+
+            $body
         })
     }};
 }
@@ -323,7 +850,7 @@ macro_rules! gen_impl {
 pub mod __private {
     use std::pin::Pin;
 
-    use crate::GeneratorToken;
+    use crate::{GeneratorToken, GeneratorTryIter, GeneratorTryStream, Recursive};
 
     // separate exports ..
     #[allow(dead_code)]
@@ -332,19 +859,109 @@ pub mod __private {
     pub use std::future::Future;
     pub use std::pin::pin;
 
-    pub use crate::detail::{RawGeneratorToken, TokenMarker};
+    pub use crate::detail::{
+        inline_budget, BoundedState, GeneratorInfo, RawGeneratorToken, TokenMarker,
+    };
     pub use crate::export::{AsyncGenerator, SyncGenerator};
+    pub use crate::introspect::YieldSite;
+    pub use crate::recursive::RecursiveStep;
 
-    pub fn gen_sync<F, Y, A>(_: TokenMarker<Y, A>, future: F) -> SyncGenerator<F, Y, A> {
-        SyncGenerator::new(future)
+    pub fn gen_sync<F, Y, A>(
+        _: TokenMarker<Y, A>,
+        info: &'static GeneratorInfo,
+        future: F,
+    ) -> SyncGenerator<F, Y, A> {
+        SyncGenerator::new(info, future)
     }
 
-    pub fn gen_async<F, Y, A>(_: TokenMarker<Y, A>, future: F) -> AsyncGenerator<F, Y, A> {
-        AsyncGenerator::new(future)
+    pub fn gen_async<F, Y, A>(
+        _: TokenMarker<Y, A>,
+        info: &'static GeneratorInfo,
+        future: F,
+    ) -> AsyncGenerator<F, Y, A> {
+        AsyncGenerator::new(info, future)
     }
 
-    pub fn token<Y, A>() -> RawGeneratorToken<Y, A> {
-        RawGeneratorToken::new()
+    pub fn sync_with_size_hint<F, Y, A>(
+        gen: SyncGenerator<F, Y, A>,
+        size_hint: (usize, Option<usize>),
+    ) -> SyncGenerator<F, Y, A> {
+        gen.with_size_hint(size_hint)
+    }
+
+    pub fn async_with_size_hint<F, Y, A>(
+        gen: AsyncGenerator<F, Y, A>,
+        size_hint: (usize, Option<usize>),
+    ) -> AsyncGenerator<F, Y, A> {
+        gen.with_size_hint(size_hint)
+    }
+
+    pub fn try_iter<F, Y, E>(
+        gen: SyncGenerator<F, Y, ()>,
+    ) -> GeneratorTryIter<SyncGenerator<F, Y, ()>>
+    where
+        F: Future<Output = Result<(), E>>,
+    {
+        GeneratorTryIter::new(gen)
+    }
+
+    pub fn try_stream<F, Y, E>(
+        gen: AsyncGenerator<F, Y, ()>,
+    ) -> GeneratorTryStream<AsyncGenerator<F, Y, ()>>
+    where
+        F: Future<Output = Result<(), E>>,
+    {
+        GeneratorTryStream::new(gen)
+    }
+
+    /// The status of the generator being resumed on this thread, if any.
+    pub fn current_status() -> Option<crate::GeneratorStatus> {
+        crate::detail::GeneratorWaker::current_status()
+    }
+
+    pub fn boxed<F: Future>(future: F) -> Pin<Box<F>> {
+        Box::pin(future)
+    }
+
+    pub fn boxed_within<const N: usize, F: Future>(future: F) -> BoundedState<F, N> {
+        BoundedState::new(future)
+    }
+
+    pub fn boxed_dyn<'a, F>(future: F) -> Pin<Box<dyn Future<Output = ()> + 'a>>
+    where
+        F: Future<Output = ()> + 'a,
+    {
+        Box::pin(future)
+    }
+
+    pub fn boxed_dyn_send<'a, F>(future: F) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
+    where
+        F: Future<Output = ()> + Send + 'a,
+    {
+        Box::pin(future)
+    }
+
+    /// Constrain the output type of the future for a generator closure with an
+    /// explicit return type.
+    pub fn returns<R, F: Future<Output = R>>(future: F) -> F {
+        future
+    }
+
+    pub fn gen_recursive<'a, F, Y, R>(
+        _: TokenMarker<RecursiveStep<'a, Y, R>, Option<R>>,
+        info: &'static GeneratorInfo,
+        future: F,
+    ) -> Recursive<'a, Y, R>
+    where
+        F: Future<Output = R> + 'a,
+        Y: 'a,
+        R: 'a,
+    {
+        Recursive::new(info, future)
+    }
+
+    pub fn token<Y, A>(info: &'static GeneratorInfo) -> RawGeneratorToken<Y, A> {
+        RawGeneratorToken::new(info)
     }
 
     pub async fn register<Y, A>(token: Pin<&RawGeneratorToken<Y, A>>) {
@@ -354,10 +971,20 @@ pub mod __private {
         unsafe { token.register().await }
     }
 
-    pub async fn register_owned<Y, A>(token: RawGeneratorToken<Y, A>) -> GeneratorToken<Y, A> {
-        // SAFETY: register_owned is only called from the code emitted by the gen!
+    pub async fn register_token<Y, A>(token: RawGeneratorToken<Y, A>) -> GeneratorToken<Y, A> {
+        // SAFETY: register_token is only called from the code emitted by the gen!
         //         macro. The macro takes responsibility for ensuring that the
         //         parameters match.
         unsafe { GeneratorToken::register(token).await }
     }
+
+    pub async fn recurse<'a, Y, R>(
+        token: Pin<&RawGeneratorToken<RecursiveStep<'a, Y, R>, Option<R>>>,
+        child: Recursive<'a, Y, R>,
+    ) -> R {
+        match token.yield_(RecursiveStep::Recurse(child)).await {
+            Some(value) => value,
+            None => panic!("recursive generator resumed without the result of its child"),
+        }
+    }
 }
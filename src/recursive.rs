@@ -0,0 +1,200 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use crate::detail::GeneratorInfo;
+use crate::export::SyncGenerator;
+use crate::{Generator, GeneratorState, YieldHint};
+
+#[cfg(feature = "macros")]
+use crate::generator;
+
+#[cfg(feature = "macros")]
+used_in_docs!(generator);
+
+type Frame<'a, Y, R> =
+    Pin<Box<dyn Generator<Option<R>, Yield = RecursiveStep<'a, Y, R>, Return = R> + 'a>>;
+
+/// A recursive generator driven by a trampoline.
+///
+/// This is the type returned by functions declared with
+/// `#[generator(recursive)]`. Instead of having each level of recursion poll
+/// the level below it, a `Recursive` generator keeps an explicit stack of
+/// suspended generators and only ever resumes the one at the top. This means
+/// that yielding a value costs the same no matter how deep the recursion is
+/// and that deep recursion cannot overflow the native stack.
+///
+/// Within a recursive generator the `recurse!` macro runs another recursive
+/// generator to completion, yielding all of its values, and then evaluates to
+/// its return value. `yield_from_boxed!` does the same for a generator that
+/// isn't recursive, boxing it with [`Recursive::boxed`] so that it can be
+/// pushed onto the stack.
+///
+/// A `Recursive` generator can only be used as an [`Iterator`] if it returns
+/// `()`. Otherwise it has to be resumed through [`Generator`] to get at its
+/// return value.
+///
+/// # Example
+/// ```
+/// struct Node {
+///     value: u32,
+///     children: Vec<Node>,
+/// }
+///
+/// #[fauxgen::generator(yield = u32, recursive)]
+/// fn walk(node: &Node) {
+///     r#yield!(node.value);
+///
+///     for child in &node.children {
+///         recurse!(walk(child));
+///     }
+/// }
+///
+/// let tree = Node {
+///     value: 1,
+///     children: vec![
+///         Node { value: 2, children: vec![] },
+///         Node { value: 3, children: vec![Node { value: 4, children: vec![] }] },
+///     ],
+/// };
+///
+/// let values: Vec<_> = walk(&tree).collect();
+/// assert_eq!(values, [1, 2, 3, 4]);
+/// ```
+///
+/// Each level of recursion is boxed, but since the type of a `Recursive`
+/// generator does not depend on its body there is no need to write
+/// `Box::pin` anywhere within the generator itself.
+#[must_use = "generators are lazy and do nothing unless consumed"]
+pub struct Recursive<'a, Y, R = ()> {
+    stack: Vec<Frame<'a, Y, R>>,
+}
+
+impl<'a, Y, R> Recursive<'a, Y, R> {
+    pub(crate) fn new<F>(info: &'static GeneratorInfo, future: F) -> Self
+    where
+        F: Future<Output = R> + 'a,
+        Y: 'a,
+        R: 'a,
+    {
+        let frame: Frame<'a, Y, R> = Box::pin(SyncGenerator::new(info, future));
+
+        Self { stack: vec![frame] }
+    }
+
+    /// Wrap a generator that isn't recursive so that it can be run by a
+    /// recursive generator using `recurse!`.
+    ///
+    /// This is what `yield_from_boxed!` does with its argument. The values of
+    /// `gen` are yielded as-is and it does not recurse any further.
+    pub fn boxed<G>(gen: G) -> Self
+    where
+        G: Generator<Yield = Y, Return = R> + 'a,
+    {
+        let frame: Frame<'a, Y, R> = Box::pin(Leaf {
+            gen,
+            _marker: PhantomData,
+        });
+
+        Self { stack: vec![frame] }
+    }
+
+    /// The number of generators currently on the stack.
+    ///
+    /// This is `1` for a generator that has not recursed yet and `0` once the
+    /// generator has completed.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+impl<Y, R> Generator for Recursive<'_, Y, R> {
+    type Yield = Y;
+    type Return = R;
+
+    fn resume(self: Pin<&mut Self>, _: ()) -> GeneratorState<Self::Yield, Self::Return> {
+        let this = self.get_mut();
+        let mut arg = None;
+
+        loop {
+            let top = this
+                .stack
+                .last_mut()
+                .expect("recursive generator resumed after completion");
+
+            match top.as_mut().resume(arg.take()) {
+                GeneratorState::Yielded(RecursiveStep::Yield(value)) => {
+                    return GeneratorState::Yielded(value)
+                }
+                GeneratorState::Yielded(RecursiveStep::Recurse(child)) => {
+                    this.stack.extend(child.stack);
+                }
+                GeneratorState::Complete(value) => {
+                    this.stack.pop();
+
+                    if this.stack.is_empty() {
+                        return GeneratorState::Complete(value);
+                    }
+
+                    arg = Some(value);
+                }
+            }
+        }
+    }
+}
+
+// The size hint of each frame counts the generators it recurses into as well as
+// the values it yields, so it says nothing about the values left overall.
+impl<Y, R> YieldHint for Recursive<'_, Y, R> {}
+
+impl<Y> Iterator for Recursive<'_, Y, ()> {
+    type Item = Y;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // A completed generator has an empty stack. Iterators are expected to keep
+        // returning None so we avoid resuming it again.
+        if self.stack.is_empty() {
+            return None;
+        }
+
+        match Pin::new(self).resume(()) {
+            GeneratorState::Yielded(value) => Some(value),
+            GeneratorState::Complete(()) => None,
+        }
+    }
+}
+
+/// A frame of a [`Recursive`] generator that runs a generator which isn't
+/// recursive.
+struct Leaf<'a, G> {
+    gen: G,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, G> Generator<Option<G::Return>> for Leaf<'a, G>
+where
+    G: Generator,
+{
+    type Yield = RecursiveStep<'a, G::Yield, G::Return>;
+    type Return = G::Return;
+
+    fn resume(
+        self: Pin<&mut Self>,
+        _: Option<G::Return>,
+    ) -> GeneratorState<Self::Yield, Self::Return> {
+        // SAFETY: The generator is never moved out of the leaf.
+        let gen = unsafe { self.map_unchecked_mut(|this| &mut this.gen) };
+
+        match gen.resume(()) {
+            GeneratorState::Yielded(value) => GeneratorState::Yielded(RecursiveStep::Yield(value)),
+            GeneratorState::Complete(value) => GeneratorState::Complete(value),
+        }
+    }
+}
+
+/// A single step taken by one frame of a [`Recursive`] generator.
+#[doc(hidden)]
+pub enum RecursiveStep<'a, Y, R> {
+    Yield(Y),
+    Recurse(Recursive<'a, Y, R>),
+}
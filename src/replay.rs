@@ -0,0 +1,174 @@
+use std::pin::Pin;
+
+use crate::{Generator, GeneratorState, YieldHint};
+
+/// A generator that can be forked by replaying its arguments.
+///
+/// Generators cannot be cloned since the future underneath them cannot be
+/// cloned. `Replayable` works around this by recording every argument passed
+/// to [`resume`](Generator::resume). Calling [`fork`](Replayable::fork)
+/// creates a fresh generator using the factory function and then fast-forwards
+/// it by resuming it with each recorded argument in turn.
+///
+/// This only works if the generator is deterministic: given the same
+/// arguments it must yield the same values. In debug builds the values yielded
+/// while replaying are checked against the ones yielded originally and a
+/// mismatch will result in a panic.
+///
+/// Note that forking a generator that has resumed `n` times costs `n` resumes.
+///
+/// # Example
+/// ```
+/// use fauxgen::{Generator, GeneratorState, Replayable};
+/// use std::pin::Pin;
+///
+/// #[fauxgen::generator(yield = u32, arg = u32)]
+/// fn running_total() {
+///     let mut total = argument!();
+///
+///     loop {
+///         total += r#yield!(total);
+///     }
+/// }
+///
+/// let mut gen = Replayable::new(running_total);
+/// assert_eq!(Pin::new(&mut gen).resume(1), GeneratorState::Yielded(1));
+/// assert_eq!(Pin::new(&mut gen).resume(2), GeneratorState::Yielded(3));
+///
+/// let mut fork = gen.fork();
+/// assert_eq!(Pin::new(&mut gen).resume(10), GeneratorState::Yielded(13));
+/// assert_eq!(Pin::new(&mut fork).resume(20), GeneratorState::Yielded(23));
+/// ```
+pub struct Replayable<F, G, A>
+where
+    G: Generator<A>,
+{
+    factory: F,
+    gen: Pin<Box<G>>,
+    args: Vec<A>,
+
+    /// The state returned by each call to resume. `None` indicates that the
+    /// generator completed.
+    #[cfg(debug_assertions)]
+    states: Vec<Option<G::Yield>>,
+}
+
+/// The values that a [`Replayable`] generator can yield.
+///
+/// In debug builds the values yielded while replaying a generator are checked
+/// against the ones it yielded originally, so they need to be [`Clone`] and
+/// [`PartialEq`]. Release builds skip the check and accept any value.
+#[cfg(debug_assertions)]
+pub trait ReplayValue: Clone + PartialEq {}
+
+#[cfg(debug_assertions)]
+impl<T: Clone + PartialEq> ReplayValue for T {}
+
+/// The values that a [`Replayable`] generator can yield.
+///
+/// In debug builds the values yielded while replaying a generator are checked
+/// against the ones it yielded originally, so they need to be [`Clone`] and
+/// [`PartialEq`]. Release builds skip the check and accept any value.
+#[cfg(not(debug_assertions))]
+pub trait ReplayValue {}
+
+#[cfg(not(debug_assertions))]
+impl<T> ReplayValue for T {}
+
+impl<F, G, A> Replayable<F, G, A>
+where
+    F: Fn() -> G,
+    G: Generator<A>,
+{
+    /// Create a new generator using `factory`.
+    ///
+    /// `factory` will be called again each time the generator is forked.
+    pub fn new(factory: F) -> Self {
+        Self {
+            gen: Box::pin(factory()),
+            factory,
+            args: Vec::new(),
+            #[cfg(debug_assertions)]
+            states: Vec::new(),
+        }
+    }
+
+    /// The arguments that have been passed to this generator so far.
+    pub fn history(&self) -> &[A] {
+        &self.args
+    }
+
+    /// Create a new generator in the same state as this one.
+    ///
+    /// # Panics
+    /// In debug builds, this panics if the new generator does not yield the
+    /// same values as this one did when it is replayed.
+    pub fn fork(&self) -> Self
+    where
+        F: Clone,
+        A: Clone,
+        G::Yield: ReplayValue,
+    {
+        let mut gen = Box::pin((self.factory)());
+
+        #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+        for (index, arg) in self.args.iter().enumerate() {
+            let state = match gen.as_mut().resume(arg.clone()) {
+                GeneratorState::Yielded(value) => Some(value),
+                GeneratorState::Complete(_) => None,
+            };
+
+            #[cfg(debug_assertions)]
+            assert!(
+                state == self.states[index],
+                "replayed generator diverged from the original after {index} resumes",
+            );
+        }
+
+        Self {
+            factory: self.factory.clone(),
+            gen,
+            args: self.args.clone(),
+            #[cfg(debug_assertions)]
+            states: self.states.clone(),
+        }
+    }
+}
+
+impl<F, G, A> Generator<A> for Replayable<F, G, A>
+where
+    G: Generator<A>,
+    G::Yield: ReplayValue,
+    A: Clone,
+{
+    type Yield = G::Yield;
+    type Return = G::Return;
+
+    fn resume(self: Pin<&mut Self>, arg: A) -> GeneratorState<Self::Yield, Self::Return> {
+        let this = self.get_mut();
+        let state = this.gen.as_mut().resume(arg.clone());
+
+        // The argument is only recorded once the generator has finished with it, so
+        // that a panic leaves the history consistent with the states recorded.
+        this.args.push(arg);
+
+        #[cfg(debug_assertions)]
+        this.states.push(match &state {
+            GeneratorState::Yielded(value) => Some(value.clone()),
+            GeneratorState::Complete(_) => None,
+        });
+
+        state
+    }
+}
+
+impl<F, G, A> Unpin for Replayable<F, G, A> where G: Generator<A> {}
+
+impl<F, G, A> YieldHint for Replayable<F, G, A>
+where
+    G: Generator<A> + YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.gen.yield_hint()
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use crate::{AsyncGenerator, GeneratorState, YieldHint};
+
+/// What a [`share`]d generator does when its buffer is full.
+///
+/// The buffer becomes full when the fastest consumer gets `capacity` values
+/// ahead of the slowest one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LagPolicy {
+    /// Wait for the slowest consumer to catch up before resuming the
+    /// generator again.
+    Block,
+
+    /// Drop the oldest value in the buffer. Consumers that had not yet seen it
+    /// silently skip ahead to the oldest value still in the buffer.
+    DropOldest,
+
+    /// Drop the oldest value in the buffer. Consumers that had not yet seen it
+    /// complete with a [`Lagged`] error the next time they are resumed.
+    Error,
+}
+
+/// The error returned by a [`Share`] branch that fell too far behind when
+/// using [`LagPolicy::Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lagged {
+    /// The number of values that were dropped before this branch could see
+    /// them.
+    pub skipped: usize,
+}
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shared generator consumer lagged behind by {} values",
+            self.skipped
+        )
+    }
+}
+
+impl std::error::Error for Lagged {}
+
+/// Split an async generator into `n` generators that each see every value it
+/// yields.
+///
+/// At most `capacity` values are buffered between the fastest and slowest
+/// consumers. What happens once the buffer is full is determined by `policy`.
+/// Each branch completes with `Ok` containing a clone of the generator's
+/// return value, or with `Err` if it lagged behind under [`LagPolicy::Error`].
+///
+/// Branches can be resumed concurrently from different tasks. Whichever
+/// branch needs the next value will resume the underlying generator.
+///
+/// # Panics
+/// Panics if `capacity` is zero.
+///
+/// # Example
+/// Since each branch returns a `Result` it can be turned into a stream using
+/// [`GeneratorTryStream`](crate::GeneratorTryStream):
+/// ```
+/// use fauxgen::{GeneratorTryStream, LagPolicy};
+/// use futures_util::StreamExt;
+///
+/// #[fauxgen::generator(yield = u32)]
+/// async fn numbers() {
+///     r#yield!(1);
+///     r#yield!(2);
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut branches = fauxgen::share(numbers(), 2, 16, LagPolicy::Block);
+/// let metrics = GeneratorTryStream::new(branches.pop().unwrap());
+/// let storage = GeneratorTryStream::new(branches.pop().unwrap());
+///
+/// let (a, b) = futures_util::join!(metrics.collect::<Vec<_>>(), storage.collect::<Vec<_>>());
+/// assert_eq!(a, [Ok(1), Ok(2)]);
+/// assert_eq!(b, [Ok(1), Ok(2)]);
+/// # }
+/// ```
+pub fn share<G>(gen: G, n: usize, capacity: usize, policy: LagPolicy) -> Vec<Share<G>>
+where
+    G: AsyncGenerator,
+{
+    assert!(capacity > 0, "shared generator capacity must be non-zero");
+
+    let state = Arc::new(Mutex::new(ShareState {
+        gen: Box::pin(gen),
+        buffer: VecDeque::new(),
+        offset: 0,
+        capacity,
+        policy,
+        cursors: vec![Some(0); n],
+        wakers: vec![None; n],
+        complete: None,
+    }));
+
+    (0..n)
+        .map(|index| Share {
+            state: state.clone(),
+            index,
+        })
+        .collect()
+}
+
+/// One branch of an async generator that has been split using [`share`].
+#[must_use = "generators are lazy and do nothing unless polled"]
+pub struct Share<G: AsyncGenerator> {
+    state: Arc<Mutex<ShareState<G>>>,
+    index: usize,
+}
+
+struct ShareState<G: AsyncGenerator> {
+    gen: Pin<Box<G>>,
+
+    /// Values that have been yielded but not yet seen by every branch.
+    buffer: VecDeque<G::Yield>,
+
+    /// The index of the first value in `buffer` within the overall sequence of
+    /// values yielded by the generator.
+    offset: usize,
+    capacity: usize,
+    policy: LagPolicy,
+
+    /// The index of the next value to be read by each branch, or `None` if the
+    /// branch has been dropped or has completed with an error.
+    cursors: Vec<Option<usize>>,
+
+    /// Wakers for branches that are waiting on another branch.
+    wakers: Vec<Option<Waker>>,
+
+    complete: Option<G::Return>,
+}
+
+impl<G: AsyncGenerator> ShareState<G> {
+    /// Read the value at `pos` on behalf of the branch at `index`.
+    fn read(&mut self, index: usize, pos: usize) -> G::Yield
+    where
+        G::Yield: Clone,
+    {
+        self.cursors[index] = Some(pos + 1);
+
+        let needed = self.cursors.iter().flatten().any(|&cursor| cursor <= pos);
+
+        if !needed && pos == self.offset {
+            self.offset += 1;
+            self.wake_all();
+            return self.buffer.pop_front().expect("share buffer was empty");
+        }
+
+        let value = self.buffer[pos - self.offset].clone();
+        self.trim();
+        value
+    }
+
+    /// Drop any values from the buffer which have been seen by every branch.
+    fn trim(&mut self) {
+        let min = self.cursors.iter().flatten().copied().min();
+        let min = min.unwrap_or(self.offset + self.buffer.len());
+
+        if self.offset >= min {
+            return;
+        }
+
+        while self.offset < min {
+            self.buffer.pop_front();
+            self.offset += 1;
+        }
+
+        self.wake_all();
+    }
+
+    fn wake_all(&mut self) {
+        for waker in self.wakers.iter_mut().filter_map(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+impl<G: AsyncGenerator> Share<G> {
+    fn lock(&self) -> MutexGuard<'_, ShareState<G>> {
+        self.state
+            .lock()
+            .expect("shared generator panicked while being resumed")
+    }
+}
+
+impl<G> AsyncGenerator for Share<G>
+where
+    G: AsyncGenerator,
+    G::Yield: Clone,
+    G::Return: Clone,
+{
+    type Yield = G::Yield;
+    type Return = Result<G::Return, Lagged>;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        _: Option<()>,
+    ) -> Poll<GeneratorState<Self::Yield, Self::Return>> {
+        let index = self.index;
+        let mut state = self.lock();
+        let mut pos = state.cursors[index].expect("shared generator resumed after completion");
+
+        if pos < state.offset {
+            match state.policy {
+                LagPolicy::Error => {
+                    let skipped = state.offset - pos;
+
+                    state.cursors[index] = None;
+                    state.trim();
+
+                    return Poll::Ready(GeneratorState::Complete(Err(Lagged { skipped })));
+                }
+                _ => pos = state.offset,
+            }
+        }
+
+        if pos < state.offset + state.buffer.len() {
+            return Poll::Ready(GeneratorState::Yielded(state.read(index, pos)));
+        }
+
+        if let Some(value) = &state.complete {
+            return Poll::Ready(GeneratorState::Complete(Ok(value.clone())));
+        }
+
+        if state.buffer.len() >= state.capacity && state.policy == LagPolicy::Block {
+            state.wakers[index] = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        match state.gen.as_mut().poll_resume(cx, Some(())) {
+            Poll::Pending => {
+                state.wakers[index] = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Poll::Ready(GeneratorState::Yielded(value)) => {
+                // Under the other policies we make room by dropping the oldest value. Any
+                // branches that hadn't seen it will notice the next time they are resumed.
+                if state.buffer.len() >= state.capacity {
+                    state.buffer.pop_front();
+                    state.offset += 1;
+                }
+
+                state.buffer.push_back(value);
+                state.wake_all();
+                Poll::Ready(GeneratorState::Yielded(state.read(index, pos)))
+            }
+            Poll::Ready(GeneratorState::Complete(value)) => {
+                state.complete = Some(value.clone());
+                state.wake_all();
+                Poll::Ready(GeneratorState::Complete(Ok(value)))
+            }
+        }
+    }
+}
+
+impl<G> YieldHint for Share<G>
+where
+    G: AsyncGenerator + YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        let Ok(state) = self.state.lock() else {
+            return (0, None);
+        };
+        let Some(pos) = state.cursors[self.index] else {
+            return (0, Some(0));
+        };
+
+        // A branch that has lagged behind skips ahead to the oldest buffered value.
+        let buffered = state.offset + state.buffer.len() - pos.max(state.offset);
+
+        let (lower, upper) = match state.complete {
+            Some(_) => (0, Some(0)),
+            None => state.gen.yield_hint(),
+        };
+        let upper = upper.and_then(|upper| upper.checked_add(buffered));
+
+        match state.policy {
+            LagPolicy::Block => (lower.saturating_add(buffered), upper),
+            // The buffered values may be dropped before this branch gets to them.
+            _ => (0, upper),
+        }
+    }
+}
+
+impl<G: AsyncGenerator> Drop for Share<G> {
+    fn drop(&mut self) {
+        // If another branch panicked while resuming the generator then there is
+        // nothing useful we can do here.
+        if let Ok(mut state) = self.state.lock() {
+            state.cursors[self.index] = None;
+            state.wakers[self.index] = None;
+            state.trim();
+
+            // The branch being dropped may have been the one driving the generator so
+            // we need to let the others know that they should try resuming it.
+            state.wake_all();
+        }
+    }
+}
@@ -0,0 +1,26 @@
+/// The state that a generator created by the fauxgen macros is in.
+///
+/// This is returned by the `status` method on the generator types returned by
+/// the [`generator`](crate::generator) macro and the [`gen!`](crate::gen)
+/// macro.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum GeneratorStatus {
+    /// The generator has not been resumed yet.
+    NotStarted,
+
+    /// The generator yielded a value and is waiting to be resumed again.
+    Suspended,
+
+    /// The generator is being resumed, or is waiting on some other future to
+    /// complete.
+    ///
+    /// The status only stays `Running` between resumes for async generators.
+    Running,
+
+    /// The generator has returned and cannot be resumed again.
+    Complete,
+
+    /// The generator panicked while being resumed and cannot be resumed
+    /// again.
+    Poisoned,
+}
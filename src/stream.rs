@@ -3,7 +3,7 @@ use std::task::{Context, Poll};
 
 use futures_core::Stream;
 
-use crate::{AsyncGenerator, GeneratorState};
+use crate::{AsyncGenerator, GeneratorState, YieldHint};
 
 #[cfg(feature = "macros")]
 use crate::generator;
@@ -16,6 +16,7 @@ used_in_docs!(generator);
 /// The generators created by the [`generator`] macro implement [`Stream`] by
 /// default. However, other implementations of [`AsyncGenerator`] will need this
 /// wrapper type in order to be used as a stream.
+#[derive(Debug)]
 pub struct GeneratorStream<G>(G);
 
 impl<G> GeneratorStream<G> {
@@ -30,7 +31,7 @@ impl<G> GeneratorStream<G> {
 
 impl<G> Stream for GeneratorStream<G>
 where
-    G: AsyncGenerator<(), Return = ()>,
+    G: AsyncGenerator<(), Return = ()> + YieldHint,
 {
     type Item = G::Yield;
 
@@ -41,6 +42,10 @@ where
             GeneratorState::Complete(()) => None,
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.yield_hint()
+    }
 }
 
 /// Wrapper around a generator that yields values and returns a result.
@@ -70,6 +75,7 @@ where
 ///
 /// let stream = GeneratorTryStream::new(my_stream());
 /// ```
+#[derive(Debug)]
 pub struct GeneratorTryStream<G> {
     gen: G,
     done: bool,
@@ -89,7 +95,7 @@ impl<G> GeneratorTryStream<G> {
 
 impl<G, E> Stream for GeneratorTryStream<G>
 where
-    G: AsyncGenerator<(), Return = Result<(), E>>,
+    G: AsyncGenerator<(), Return = Result<(), E>> + YieldHint,
 {
     type Item = Result<G::Yield, E>;
 
@@ -117,4 +123,14 @@ where
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+
+        // Returning an error adds one more item to the end of the stream.
+        let (lower, upper) = self.gen.yield_hint();
+        (lower, upper.and_then(|upper| upper.checked_add(1)))
+    }
 }
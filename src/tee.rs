@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::{Generator, GeneratorState, YieldHint};
+
+/// Split a generator into `n` generators that each see every value it yields.
+///
+/// Values yielded by the original generator are buffered until every branch
+/// has consumed them, so a branch that falls far behind the others will cause
+/// the buffer to grow. Once the original generator completes, each branch
+/// returns a clone of its return value.
+///
+/// Dropping a branch removes it from the set of consumers so it no longer
+/// holds back the buffer.
+///
+/// # Example
+/// ```
+/// #[fauxgen::generator(yield = u32)]
+/// fn numbers() {
+///     r#yield!(1);
+///     r#yield!(2);
+///     r#yield!(3);
+/// }
+///
+/// let mut branches = fauxgen::tee(numbers(), 2);
+/// let second = branches.pop().unwrap();
+/// let first = branches.pop().unwrap();
+///
+/// assert_eq!(first.collect::<Vec<_>>(), [1, 2, 3]);
+/// assert_eq!(second.collect::<Vec<_>>(), [1, 2, 3]);
+/// ```
+pub fn tee<G>(gen: G, n: usize) -> Vec<Tee<G>>
+where
+    G: Generator,
+{
+    let state = Rc::new(RefCell::new(TeeState {
+        gen: Box::pin(gen),
+        buffer: VecDeque::new(),
+        offset: 0,
+        cursors: vec![Some(0); n],
+        complete: None,
+    }));
+
+    (0..n)
+        .map(|index| Tee {
+            state: state.clone(),
+            index,
+        })
+        .collect()
+}
+
+/// One branch of a generator that has been split using [`tee`].
+#[must_use = "generators are lazy and do nothing unless consumed"]
+pub struct Tee<G: Generator> {
+    state: Rc<RefCell<TeeState<G>>>,
+    index: usize,
+}
+
+struct TeeState<G: Generator> {
+    gen: Pin<Box<G>>,
+
+    /// Values that have been yielded but not yet seen by every branch.
+    buffer: VecDeque<G::Yield>,
+
+    /// The index of the first value in `buffer` within the overall sequence of
+    /// values yielded by the generator.
+    offset: usize,
+
+    /// The index of the next value to be read by each branch, or `None` if the
+    /// branch has been dropped.
+    cursors: Vec<Option<usize>>,
+
+    complete: Option<G::Return>,
+}
+
+impl<G: Generator> TeeState<G> {
+    /// Read the value at `pos` on behalf of the branch at `index`.
+    fn read(&mut self, index: usize, pos: usize) -> G::Yield
+    where
+        G::Yield: Clone,
+    {
+        self.cursors[index] = Some(pos + 1);
+
+        let needed = self.cursors.iter().flatten().any(|&cursor| cursor <= pos);
+
+        if !needed && pos == self.offset {
+            self.offset += 1;
+            return self.buffer.pop_front().expect("tee buffer was empty");
+        }
+
+        let value = self.buffer[pos - self.offset].clone();
+        self.trim();
+        value
+    }
+
+    /// Drop any values from the buffer which have been seen by every branch.
+    fn trim(&mut self) {
+        let min = self.cursors.iter().flatten().copied().min();
+        let min = min.unwrap_or(self.offset + self.buffer.len());
+
+        while self.offset < min {
+            self.buffer.pop_front();
+            self.offset += 1;
+        }
+    }
+}
+
+impl<G> Generator for Tee<G>
+where
+    G: Generator,
+    G::Yield: Clone,
+    G::Return: Clone,
+{
+    type Yield = G::Yield;
+    type Return = G::Return;
+
+    fn resume(self: Pin<&mut Self>, _: ()) -> GeneratorState<Self::Yield, Self::Return> {
+        let mut state = self.state.borrow_mut();
+        let pos = state.cursors[self.index].expect("tee branch has been detached");
+
+        if pos < state.offset + state.buffer.len() {
+            return GeneratorState::Yielded(state.read(self.index, pos));
+        }
+
+        if let Some(value) = &state.complete {
+            return GeneratorState::Complete(value.clone());
+        }
+
+        match state.gen.as_mut().resume(()) {
+            GeneratorState::Yielded(value) => {
+                state.buffer.push_back(value);
+                GeneratorState::Yielded(state.read(self.index, pos))
+            }
+            GeneratorState::Complete(value) => {
+                state.complete = Some(value.clone());
+                GeneratorState::Complete(value)
+            }
+        }
+    }
+}
+
+impl<G> YieldHint for Tee<G>
+where
+    G: Generator + YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        // The state is only borrowed elsewhere while a branch is being resumed, in
+        // which case there is no way to tell.
+        let Ok(state) = self.state.try_borrow() else {
+            return (0, None);
+        };
+        let Some(pos) = state.cursors[self.index] else {
+            return (0, Some(0));
+        };
+
+        let buffered = state.offset + state.buffer.len() - pos;
+        if state.complete.is_some() {
+            return (buffered, Some(buffered));
+        }
+
+        let (lower, upper) = state.gen.yield_hint();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<G> Iterator for Tee<G>
+where
+    G: Generator<Return = ()>,
+    G::Yield: Clone,
+{
+    type Item = G::Yield;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match Pin::new(self).resume(()) {
+            GeneratorState::Yielded(value) => Some(value),
+            GeneratorState::Complete(()) => None,
+        }
+    }
+}
+
+impl<G: Generator> Drop for Tee<G> {
+    fn drop(&mut self) {
+        // If the generator panicked while we were resuming it then the state is still
+        // borrowed. There's nothing useful we can do in that case.
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            state.cursors[self.index] = None;
+            state.trim();
+        }
+    }
+}
@@ -1,11 +1,13 @@
+use std::fmt;
 use std::pin::Pin;
 
 use crate::detail::RawGeneratorToken;
+use crate::Error;
 
 /// A generator token ties together the executor and the generator itself.
 ///
 /// It is what allows us to yield values back out of the generator.
-pub struct GeneratorToken<Y, A = ()>(Pin<Box<RawGeneratorToken<Y, A>>>);
+pub struct GeneratorToken<Y, A = ()>(RawGeneratorToken<Y, A>);
 
 impl<Y, A> GeneratorToken<Y, A> {
     /// Create a new GeneratorToken by registering this one.
@@ -13,23 +15,33 @@ impl<Y, A> GeneratorToken<Y, A> {
     /// # Safety
     /// The `Y` and `A` types for this token must mach those of the generator
     /// context.
-    pub(crate) async unsafe fn register(token: RawGeneratorToken<Y, A>) -> GeneratorToken<Y, A> {
-        let token = Box::pin(token);
-
+    pub(crate) async unsafe fn register(token: RawGeneratorToken<Y, A>) -> Self {
         // SAFETY: The caller of this function ensures that the requirements here are
         //         upheld.
-        token.as_ref().register().await;
+        Pin::new(&token).register().await;
 
         Self(token)
     }
 
+    fn raw(&self) -> Pin<&RawGeneratorToken<Y, A>> {
+        Pin::new(&self.0)
+    }
+
     /// Yield a value from this generator, returning control back to the caller.
     ///
+    /// If a previous yield was cancelled (e.g. by `select!`) after its value
+    /// was yielded, the next argument passed to the generator is its reply.
+    /// That argument has to be read with [`argument`] before yielding again.
+    ///
     /// # Panics
-    /// Panics if evaluated in the context of a generator other than the one
-    /// this token was created for.
+    /// - Panics if evaluated in the context of a generator other than the one
+    ///   this token was created for.
+    /// - Panics if the argument for a cancelled yield has not been read. This
+    ///   doesn't apply if the argument type is `()`.
+    ///
+    /// [`argument`]: GeneratorToken::argument
     pub async fn yield_(&self, value: Y) -> A {
-        self.0.as_ref().yield_(value).await
+        self.raw().yield_(value).await
     }
 
     /// Get the current argument without yielding.
@@ -47,6 +59,88 @@ impl<Y, A> GeneratorToken<Y, A> {
     ///
     /// [`yield_`]: GeneratorToken::yield_
     pub async fn argument(&self) -> A {
-        self.0.as_ref().argument().await
+        self.raw().argument().await
+    }
+
+    /// Update the bounds on the number of values this generator has left to
+    /// yield.
+    ///
+    /// This is what [`Iterator::size_hint`] and `Stream::size_hint` return
+    /// for the generator. The hint is reduced by one each time the generator
+    /// yields a value, so it only needs to be set again if the estimate
+    /// changes.
+    ///
+    /// # Panics
+    /// Panics if evaluated in the context of a generator other than the one
+    /// this token was created for.
+    pub async fn set_size_hint(&self, lower: usize, upper: Option<usize>) {
+        self.raw().set_size_hint(lower, upper).await
+    }
+
+    /// Yield a value from this generator, returning an error instead of
+    /// panicking on misuse.
+    ///
+    /// # Errors
+    /// - [`Error::UnsupportedWaker`] if this is not being evaluated within a
+    ///   generator.
+    /// - [`Error::TokenMismatch`] if evaluated in the context of a generator
+    ///   other than the one this token was created for. The value is not
+    ///   yielded in this case.
+    /// - [`Error::NoArgument`] if the generator was resumed without an
+    ///   argument.
+    /// - [`Error::UnreadArgument`] if the argument for a cancelled yield has
+    ///   not been read. The value is not yielded, and the argument can still
+    ///   be taken with [`argument`](Self::argument).
+    pub async fn try_yield_(&self, value: Y) -> Result<A, Error> {
+        self.raw().try_yield_(value).await
+    }
+
+    /// Get the current argument without yielding, returning an error instead
+    /// of panicking on misuse.
+    ///
+    /// # Errors
+    /// - [`Error::UnsupportedWaker`] if this is not being evaluated within a
+    ///   generator.
+    /// - [`Error::TokenMismatch`] if evaluated in the context of a generator
+    ///   other than the one this token was created for.
+    /// - [`Error::NoArgument`] if there is no argument saved in the generator
+    ///   context.
+    pub async fn try_argument(&self) -> Result<A, Error> {
+        self.raw().try_argument().await
+    }
+}
+
+impl<Y> GeneratorToken<Y, ()> {
+    /// Yield every value from `iter`, only suspending the generator once.
+    ///
+    /// When the generator is used as an [`Iterator`] or a `Stream` the values
+    /// are taken directly from `iter` without resuming the generator, which is
+    /// much cheaper than yielding each value separately. Resuming the
+    /// generator through the [`Generator`](crate::Generator) trait still sees
+    /// each value as a separate yield.
+    ///
+    /// This is only available for generators that don't take an argument.
+    ///
+    /// # Panics
+    /// Panics if evaluated in the context of a generator other than the one
+    /// this token was created for.
+    pub async fn yield_batch<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = Y>,
+    {
+        self.raw().yield_batch(iter).await
+    }
+}
+
+impl<Y, A> fmt::Debug for GeneratorToken<Y, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = self.0.info();
+
+        f.debug_struct("GeneratorToken")
+            .field("name", &info.name)
+            .field("location", &format_args!("{}", info.location()))
+            .field("yield", &format_args!("{}", std::any::type_name::<Y>()))
+            .field("arg", &format_args!("{}", std::any::type_name::<A>()))
+            .finish()
     }
 }
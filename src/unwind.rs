@@ -0,0 +1,106 @@
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, UnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::{AsyncGenerator, Generator, GeneratorState, YieldHint};
+
+/// Wrapper around a generator that catches panics.
+///
+/// If the wrapped generator panics while it is being resumed then the panic is
+/// caught and the generator completes with `Err(payload)`. Otherwise it
+/// completes with `Ok(value)`.
+///
+/// The generators created by the fauxgen macros refuse to be resumed again
+/// after they have panicked so resuming a `CatchUnwind` after it has returned
+/// an error will result in a panic.
+///
+/// This works with both [`Generator`]s and [`AsyncGenerator`]s. Like
+/// [`std::panic::catch_unwind`], it requires that the wrapped generator is
+/// [`UnwindSafe`]. You can wrap the generator in [`AssertUnwindSafe`] if you
+/// know that this is not a problem.
+///
+/// # Example
+/// ```
+/// use fauxgen::{CatchUnwind, Generator, GeneratorState};
+/// use std::panic::AssertUnwindSafe;
+///
+/// #[fauxgen::generator(yield = u32)]
+/// fn fails() {
+///     r#yield!(1);
+///     panic!("oh no");
+/// }
+///
+/// let mut gen = std::pin::pin!(CatchUnwind::new(AssertUnwindSafe(fails())));
+/// assert!(matches!(gen.as_mut().resume(()), GeneratorState::Yielded(1)));
+/// assert!(matches!(gen.as_mut().resume(()), GeneratorState::Complete(Err(_))));
+/// ```
+#[derive(Debug)]
+pub struct CatchUnwind<G>(G);
+
+impl<G> CatchUnwind<G> {
+    pub fn new(gen: G) -> Self
+    where
+        G: UnwindSafe,
+    {
+        Self(gen)
+    }
+
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G, A> Generator<A> for CatchUnwind<G>
+where
+    G: Generator<A> + UnwindSafe,
+{
+    type Yield = G::Yield;
+    type Return = Result<G::Return, Box<dyn Any + Send>>;
+
+    fn resume(self: Pin<&mut Self>, arg: A) -> GeneratorState<Self::Yield, Self::Return> {
+        let gen = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| gen.resume(arg))) {
+            Ok(GeneratorState::Yielded(value)) => GeneratorState::Yielded(value),
+            Ok(GeneratorState::Complete(value)) => GeneratorState::Complete(Ok(value)),
+            Err(payload) => GeneratorState::Complete(Err(payload)),
+        }
+    }
+}
+
+impl<G, A> AsyncGenerator<A> for CatchUnwind<G>
+where
+    G: AsyncGenerator<A> + UnwindSafe,
+{
+    type Yield = G::Yield;
+    type Return = Result<G::Return, Box<dyn Any + Send>>;
+
+    fn poll_resume(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        arg: Option<A>,
+    ) -> Poll<GeneratorState<Self::Yield, Self::Return>> {
+        let gen = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| gen.poll_resume(cx, arg))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(GeneratorState::Yielded(value))) => {
+                Poll::Ready(GeneratorState::Yielded(value))
+            }
+            Ok(Poll::Ready(GeneratorState::Complete(value))) => {
+                Poll::Ready(GeneratorState::Complete(Ok(value)))
+            }
+            Err(payload) => Poll::Ready(GeneratorState::Complete(Err(payload))),
+        }
+    }
+}
+
+impl<G> YieldHint for CatchUnwind<G>
+where
+    G: YieldHint,
+{
+    fn yield_hint(&self) -> (usize, Option<usize>) {
+        self.0.yield_hint()
+    }
+}
@@ -0,0 +1,69 @@
+//! Checks that inline generators don't allocate.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use fauxgen::{gen, GeneratorToken};
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+#[test]
+fn inline_generator_does_not_allocate() {
+    let before = allocations();
+
+    let mut total = 0;
+    for limit in 0..100u32 {
+        let gen = std::pin::pin!(gen!(move |token: GeneratorToken<_>| {
+            for i in 0..limit {
+                token.yield_(i).await;
+            }
+        }));
+
+        total += gen.sum::<u32>();
+    }
+
+    assert_eq!(allocations(), before);
+    assert_eq!(total, 161700);
+}
+
+#[fauxgen::generator(yield = u32, max_size = 256, boxed_state)]
+fn bounded(limit: u32) {
+    for i in 0..limit {
+        r#yield!(i);
+    }
+}
+
+#[test]
+fn boxed_state_within_max_size_does_not_allocate() {
+    let before = allocations();
+
+    let mut total = 0;
+    for limit in 0..100u32 {
+        total += std::pin::pin!(bounded(limit)).sum::<u32>();
+    }
+
+    assert_eq!(allocations(), before);
+    assert_eq!(total, 161700);
+}
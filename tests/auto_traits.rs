@@ -0,0 +1,120 @@
+use std::cell::Cell;
+use std::future::{Future, Ready};
+use std::pin::pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use fauxgen::__private::{AsyncGenerator, SyncGenerator};
+use fauxgen::{
+    gen, BoxedIter, BoxedSendIter, BoxedSendStream, BoxedStream, Error, Generator, GeneratorIter,
+    GeneratorState, GeneratorStream, GeneratorToken, GeneratorTryIter, GeneratorTryStream, Resume,
+};
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+
+type SendFuture = Ready<()>;
+type LocalFuture = Ready<Rc<()>>;
+
+// Tokens never store values of their type parameters.
+assert_impl_all!(GeneratorToken<u32, u32>: Send, Sync);
+
+// Sync generators never store a yielded value or an argument between resumes,
+// so only the future matters.
+assert_impl_all!(SyncGenerator<SendFuture, u32, u32>: Send, Sync);
+assert_impl_all!(SyncGenerator<SendFuture, Rc<u32>, Rc<u32>>: Send, Sync);
+assert_not_impl_any!(SyncGenerator<LocalFuture, u32, u32>: Send, Sync);
+
+// Async generators hold on to the argument while the future is pending.
+assert_impl_all!(AsyncGenerator<SendFuture, u32, u32>: Send, Sync);
+assert_impl_all!(AsyncGenerator<Ready<Cell<u32>>, u32, u32>: Send, Sync);
+assert_not_impl_any!(AsyncGenerator<LocalFuture, u32, u32>: Send, Sync);
+assert_not_impl_any!(AsyncGenerator<SendFuture, Rc<u32>, u32>: Send, Sync);
+assert_not_impl_any!(AsyncGenerator<SendFuture, u32, Rc<u32>>: Send, Sync);
+
+// Resume holds both the generator and the argument.
+assert_impl_all!(Resume<'static, u32, AsyncGenerator<SendFuture, u32, u32>>: Send, Sync);
+assert_not_impl_any!(Resume<'static, Rc<u32>, AsyncGenerator<SendFuture, u32, u32>>: Send, Sync);
+
+// The wrappers are transparent.
+assert_impl_all!(GeneratorIter<SyncGenerator<SendFuture, u32, ()>>: Send, Sync);
+assert_impl_all!(GeneratorStream<AsyncGenerator<SendFuture, u32, ()>>: Send, Sync);
+assert_impl_all!(GeneratorTryStream<AsyncGenerator<SendFuture, u32, ()>>: Send, Sync);
+assert_not_impl_any!(GeneratorIter<SyncGenerator<LocalFuture, u32, ()>>: Send, Sync);
+assert_not_impl_any!(GeneratorStream<AsyncGenerator<SendFuture, Rc<u32>, ()>>: Send, Sync);
+
+// The wrappers are covariant in the generator.
+#[allow(dead_code)]
+fn wrappers_are_covariant<'a>(
+    iter: GeneratorIter<&'static str>,
+    stream: GeneratorStream<&'static str>,
+    try_iter: GeneratorTryIter<&'static str>,
+    try_stream: GeneratorTryStream<&'static str>,
+) -> (
+    GeneratorIter<&'a str>,
+    GeneratorStream<&'a str>,
+    GeneratorTryIter<&'a str>,
+    GeneratorTryStream<&'a str>,
+) {
+    (iter, stream, try_iter, try_stream)
+}
+
+// Only the `send` versions of the boxed generators can be sent to another thread.
+assert_impl_all!(BoxedSendIter<'static, u32>: Send);
+assert_impl_all!(BoxedSendStream<'static, u32>: Send);
+assert_not_impl_any!(BoxedIter<'static, u32>: Send);
+assert_not_impl_any!(BoxedStream<'static, u32>: Send);
+assert_not_impl_any!(BoxedSendStream<'static, Rc<u32>>: Send);
+
+#[fauxgen::generator(yield = u32)]
+async fn send_gen() {
+    r#yield!(1);
+}
+
+#[fauxgen::generator(yield = Rc<u32>)]
+fn local_gen() {
+    r#yield!(Rc::new(1));
+}
+
+#[test]
+fn generator_is_send() {
+    fn assert_send<T: Send + Sync>(_: &T) {}
+
+    assert_send(&send_gen());
+}
+
+#[test]
+fn local_generator_can_be_used_locally() {
+    let gen = std::pin::pin!(local_gen());
+    let values: Vec<_> = gen.map(|value| *value).collect();
+
+    assert_eq!(values, [1]);
+}
+
+#[test]
+fn token_cannot_be_used_from_another_thread() {
+    let gen = gen!(|token: GeneratorToken<Rc<u32>>| {
+        let token = &token;
+
+        // The waker is handed to a thread that isn't polling the generator, so the
+        // `Rc` must not make it into the generator from there.
+        let result = std::future::poll_fn(|cx| {
+            let waker = cx.waker();
+            let result = std::thread::scope(|scope| {
+                scope
+                    .spawn(|| {
+                        let mut cx = Context::from_waker(waker);
+                        pin!(token.try_yield_(Rc::new(1))).poll(&mut cx)
+                    })
+                    .join()
+                    .unwrap()
+            });
+
+            Poll::Ready(result)
+        })
+        .await;
+
+        assert_eq!(result, Poll::Ready(Err(Error::UnsupportedWaker)));
+    });
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(()), GeneratorState::Complete(()));
+}
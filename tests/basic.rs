@@ -15,6 +15,25 @@ fn iter_basic() {
     assert_eq!(values, [5, 77, 256]);
 }
 
+#[test]
+fn iter_fold_after_next() {
+    #[generator(yield = u32)]
+    fn count(n: u32) {
+        for i in 0..n {
+            r#yield!(i);
+        }
+    }
+
+    let mut gen = std::pin::pin!(count(10));
+    assert_eq!(gen.next(), Some(0));
+    assert_eq!(gen.as_mut().sum::<u32>(), 45);
+    assert_eq!(gen.status(), fauxgen::GeneratorStatus::Complete);
+
+    let mut seen = Vec::new();
+    Box::pin(count(3)).for_each(|value| seen.push(value));
+    assert_eq!(seen, [0, 1, 2]);
+}
+
 #[test]
 fn ensure_send() {
     #[fauxgen::generator(yield = i32)]
@@ -54,3 +73,45 @@ fn iter_with_lifetime() {
         })
     }
 }
+
+#[test]
+fn arg_param() {
+    use fauxgen::{Generator, GeneratorState};
+
+    #[generator(yield = String)]
+    fn echo(prefix: &'static str, #[arg] mut value: u32) -> u32 {
+        for _ in 0..2 {
+            value += r#yield!(format!("{prefix}{value}"));
+        }
+        value
+    }
+
+    let mut gen = std::pin::pin!(echo("value: "));
+    assert_eq!(
+        gen.as_mut().resume(1),
+        GeneratorState::Yielded("value: 1".into())
+    );
+    assert_eq!(
+        gen.as_mut().resume(2),
+        GeneratorState::Yielded("value: 3".into())
+    );
+    assert_eq!(gen.as_mut().resume(3), GeneratorState::Complete(6));
+}
+
+#[test]
+fn arg_param_with_lifetime() {
+    use fauxgen::{Generator, GeneratorState};
+
+    #[generator(yield = usize, arg = &'a str)]
+    fn lengths<'a>(#[arg] first: &'a str) {
+        let mut word = first;
+        while !word.is_empty() {
+            word = r#yield!(word.len());
+        }
+    }
+
+    let mut gen = std::pin::pin!(lengths());
+    assert_eq!(gen.as_mut().resume("four"), GeneratorState::Yielded(4));
+    assert_eq!(gen.as_mut().resume("two"), GeneratorState::Yielded(3));
+    assert_eq!(gen.as_mut().resume(""), GeneratorState::Complete(()));
+}
@@ -0,0 +1,132 @@
+use std::pin::pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use fauxgen::{gen, AsyncGenerator, Generator, GeneratorState, GeneratorStatus, GeneratorToken};
+
+#[fauxgen::generator(yield = u32)]
+fn mixed() {
+    r#yield!(0);
+    yield_batch!([1, 2, 3]);
+    yield_batch!(Vec::new());
+    r#yield!(4);
+    yield_batch!(5..7);
+}
+
+#[test]
+fn iterate_batches() {
+    let values: Vec<_> = pin!(mixed()).collect();
+    assert_eq!(values, [0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn resume_sees_each_value() {
+    let mut gen = pin!(mixed());
+
+    for expected in 0..7 {
+        match gen.as_mut().resume(()) {
+            GeneratorState::Yielded(value) => assert_eq!(value, expected),
+            GeneratorState::Complete(()) => panic!("generator completed early"),
+        }
+
+        assert_eq!(gen.status(), GeneratorStatus::Suspended);
+    }
+
+    assert!(matches!(
+        gen.as_mut().resume(()),
+        GeneratorState::Complete(())
+    ));
+}
+
+#[test]
+fn fold_after_partial_batch() {
+    let mut gen = pin!(mixed());
+    gen.next();
+    gen.next();
+
+    assert_eq!(gen.as_mut().sum::<u32>(), 2 + 3 + 4 + 5 + 6);
+    assert_eq!(gen.status(), GeneratorStatus::Complete);
+}
+
+#[test]
+fn inline_batch() {
+    let gen = gen!(|token: GeneratorToken<_>| {
+        token.yield_batch("abc".chars()).await;
+        token.yield_('d').await;
+    });
+
+    assert_eq!(pin!(gen).collect::<String>(), "abcd");
+}
+
+#[test]
+fn drop_during_batch() {
+    let value = Rc::new(());
+
+    {
+        let value = value.clone();
+        let gen = gen!(|token: GeneratorToken<_>| {
+            token.yield_batch(std::iter::repeat(value)).await;
+        });
+        let mut gen = pin!(gen);
+
+        assert!(gen.next().is_some());
+        assert!(gen.next().is_some());
+    }
+
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn panic_during_batch() {
+    let gen = gen!(|token: GeneratorToken<_>| {
+        let values = [1, 2].into_iter().inspect(|&value| {
+            if value == 2 {
+                panic!("batch failed");
+            }
+        });
+        token.yield_batch(values).await;
+    });
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.next(), Some(1));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| gen.next()));
+    assert!(result.is_err());
+    assert_eq!(gen.status(), GeneratorStatus::Poisoned);
+}
+
+#[test]
+fn cancelled_batch() {
+    let gen = gen!(async |token: GeneratorToken<_>| {
+        {
+            // Polling the batch hands it over to the wrapper. Dropping it afterwards
+            // must take it back so that none of its values are yielded.
+            let mut batch = pin!(token.yield_batch(vec![1, 2, 3]));
+            assert!(futures_util::poll!(batch.as_mut()).is_pending());
+        }
+
+        token.yield_(4).await;
+    });
+    let mut gen = pin!(gen);
+    let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+
+    assert_eq!(
+        gen.as_mut().poll_resume(&mut cx, Some(())),
+        Poll::Ready(GeneratorState::Yielded(4))
+    );
+}
+
+#[tokio::test]
+async fn stream_batches() {
+    use futures_util::StreamExt;
+
+    #[fauxgen::generator(yield = u32)]
+    async fn numbers() {
+        yield_batch!(0..3);
+        tokio::task::yield_now().await;
+        yield_batch!(3..5);
+    }
+
+    let values: Vec<_> = numbers().collect().await;
+    assert_eq!(values, [0, 1, 2, 3, 4]);
+}
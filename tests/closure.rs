@@ -0,0 +1,101 @@
+use std::pin::pin;
+
+use fauxgen::{Generator, GeneratorState};
+
+#[test]
+fn captures_locals() {
+    let offset = 10;
+    let numbers = fauxgen::closure!(
+        #[generator(yield = u32)]
+        |count: u32| {
+            for i in 0..count {
+                r#yield!(i + offset);
+            }
+        }
+    );
+
+    // Captures that are Copy leave the closure callable more than once.
+    assert_eq!(pin!(numbers(3)).collect::<Vec<_>>(), [10, 11, 12]);
+    assert_eq!(pin!(numbers(1)).collect::<Vec<_>>(), [10]);
+}
+
+#[test]
+fn moves_captures() {
+    let words = vec!["a".to_string(), "b".to_string()];
+    let gen = fauxgen::closure!(
+        #[fauxgen::generator(yield = String)]
+        move || {
+            for word in words {
+                r#yield!(word);
+            }
+        }
+    );
+
+    assert_eq!(pin!(gen()).collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn argument_and_return() {
+    let gen = fauxgen::closure!(
+        #[generator(yield = u32, arg = u32)]
+        |scale: u32| -> u32 {
+            let mut total = argument!();
+            while total < 100 {
+                total += r#yield!(total * scale);
+            }
+            total
+        }
+    );
+    let mut gen = pin!(gen(2));
+
+    assert_eq!(gen.as_mut().resume(10), GeneratorState::Yielded(20));
+    assert_eq!(gen.as_mut().resume(50), GeneratorState::Yielded(120));
+    assert_eq!(gen.as_mut().resume(50), GeneratorState::Complete(110));
+}
+
+#[test]
+fn size_hint_and_batches() {
+    let gen = fauxgen::closure!(
+        #[generator(yield = u32, size_hint = (count, Some(count)))]
+        |count: usize| {
+            yield_batch!(0..count as u32);
+        }
+    );
+    let gen = pin!(gen(5));
+
+    assert_eq!(gen.size_hint(), (5, Some(5)));
+    assert_eq!(gen.sum::<u32>(), 10);
+}
+
+#[test]
+fn debug_names_closure() {
+    let gen = fauxgen::closure!(
+        #[generator(yield = u32)]
+        || {
+            r#yield!(1);
+        }
+    );
+    let gen = gen();
+
+    let debug = format!("{gen:?}");
+    assert!(debug.contains("closure::{closure}"), "{debug}");
+    assert!(debug.contains("tests/closure.rs"), "{debug}");
+}
+
+#[tokio::test]
+async fn async_closure() {
+    use futures_util::StreamExt;
+
+    let gen = fauxgen::closure!(
+        #[generator(yield = u32)]
+        async |count: u32| {
+            for i in 0..count {
+                tokio::task::yield_now().await;
+                r#yield!(i);
+            }
+        }
+    );
+
+    let values: Vec<_> = gen(3).collect().await;
+    assert_eq!(values, [0, 1, 2]);
+}
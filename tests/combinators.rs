@@ -0,0 +1,127 @@
+use std::pin::pin;
+
+use fauxgen::{gen, Error, Generator, GeneratorState, GeneratorToken};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+
+#[test]
+fn yield_within_join() {
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        let first = async {
+            token.yield_(1).await;
+            token.yield_(2).await;
+        };
+        let second = async {
+            token.yield_(10).await;
+            token.yield_(20).await;
+        };
+
+        futures_util::join!(first, second);
+    });
+
+    // The first yield to be polled goes first and the others wait until it has
+    // received its argument.
+    let values: Vec<_> = pin!(gen).collect();
+    assert_eq!(values, [1, 2, 10, 20]);
+}
+
+#[test]
+fn arguments_within_join() {
+    let gen = gen!(|token: GeneratorToken<&'static str, u32>| {
+        let (a, b) = futures_util::join!(token.yield_("a"), token.yield_("b"));
+        token.yield_("done").await;
+        a * 10 + b
+    });
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(0), GeneratorState::Yielded("a"));
+    assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded("b"));
+    assert_eq!(gen.as_mut().resume(2), GeneratorState::Yielded("done"));
+    assert_eq!(gen.as_mut().resume(3), GeneratorState::Complete(12));
+}
+
+#[test]
+fn yield_within_futures_unordered() {
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        let token = &token;
+        let mut tasks: FuturesUnordered<_> = (0..3)
+            .map(|i| async move {
+                token.yield_(i).await;
+                token.yield_(i + 10).await;
+            })
+            .collect();
+
+        while tasks.next().await.is_some() {}
+    });
+
+    let mut values: Vec<_> = pin!(gen).collect();
+    assert_eq!(values.len(), 6);
+
+    values.sort();
+    assert_eq!(values, [0, 1, 2, 10, 11, 12]);
+}
+
+#[test]
+fn batch_within_join() {
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        futures_util::join!(token.yield_batch([1, 2, 3]), token.yield_(4));
+    });
+
+    let values: Vec<_> = pin!(gen).collect();
+    assert_eq!(values, [1, 2, 3, 4]);
+}
+
+#[test]
+fn cancelled_yield() {
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        {
+            // The yield is polled first, so its value has already been handed over by
+            // the time the other future completes and it is dropped.
+            let yielded = pin!(token.yield_(1));
+            futures_util::future::select(yielded, std::future::ready(())).await;
+        }
+
+        token.yield_(2).await;
+    });
+
+    let values: Vec<_> = pin!(gen).collect();
+    assert_eq!(values, [1, 2]);
+}
+
+#[test]
+fn cancelled_yield_keeps_argument() {
+    let gen = gen!(|token: GeneratorToken<u32, u32>| {
+        {
+            let yielded = pin!(token.yield_(1));
+            futures_util::future::select(yielded, std::future::ready(())).await;
+        }
+
+        // The argument passed back for the cancelled yield has to be read before
+        // yielding again.
+        assert_eq!(token.try_yield_(2).await, Err(Error::UnreadArgument));
+        let reply = token.argument().await;
+
+        token.yield_(reply * 2).await
+    });
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(0), GeneratorState::Yielded(1));
+    assert_eq!(gen.as_mut().resume(10), GeneratorState::Yielded(20));
+    assert_eq!(gen.as_mut().resume(7), GeneratorState::Complete(7));
+}
+
+#[tokio::test]
+async fn yield_within_select() {
+    let gen = gen!(async |token: GeneratorToken<u32>| {
+        let mut sleep = pin!(tokio::time::sleep(std::time::Duration::from_secs(60)));
+
+        for i in 0..3 {
+            tokio::select! {
+                _ = token.yield_(i) => (),
+                _ = &mut sleep => unreachable!(),
+            }
+        }
+    });
+
+    let values: Vec<_> = gen.collect().await;
+    assert_eq!(values, [0, 1, 2]);
+}
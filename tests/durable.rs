@@ -0,0 +1,65 @@
+#![cfg(feature = "serde")]
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use fauxgen::{Durable, GeneratorState};
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn accumulate() -> u32 {
+    let mut total = argument!();
+
+    while total < 100 {
+        total += r#yield!(total);
+    }
+
+    total
+}
+
+fn journal_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fauxgen-{name}-{}.journal", std::process::id()))
+}
+
+#[test]
+fn recover_after_torn_write() {
+    let path = journal_path("torn");
+
+    let mut gen = Durable::create(&path, accumulate())
+        .unwrap()
+        .record_yields();
+    assert_eq!(gen.try_resume(10).unwrap(), GeneratorState::Yielded(10));
+    assert_eq!(gen.try_resume(20).unwrap(), GeneratorState::Yielded(30));
+    drop(gen);
+
+    // Simulate a crash in the middle of writing an entry.
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(b"[\"arg\",4").unwrap();
+    drop(file);
+
+    let (mut gen, pending) = Durable::recover(&path, accumulate).unwrap();
+    assert_eq!(pending, Some(GeneratorState::Yielded(30)));
+    assert_eq!(gen.try_resume(40).unwrap(), GeneratorState::Yielded(70));
+    drop(gen);
+
+    let (mut gen, pending) = Durable::recover(&path, accumulate).unwrap();
+    assert_eq!(pending, Some(GeneratorState::Yielded(70)));
+    assert_eq!(gen.try_resume(50).unwrap(), GeneratorState::Complete(120));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn recover_empty_journal() {
+    let path = journal_path("empty");
+
+    drop(Durable::create(&path, accumulate()).unwrap());
+
+    let (mut gen, pending) = Durable::recover(&path, accumulate).unwrap();
+    assert_eq!(pending, None);
+    assert_eq!(gen.try_resume(5).unwrap(), GeneratorState::Yielded(5));
+
+    std::fs::remove_file(&path).unwrap();
+}
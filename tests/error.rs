@@ -0,0 +1,119 @@
+use fauxgen::{gen, Error, Generator, GeneratorState, GeneratorToken};
+
+#[test]
+fn argument_taken_twice() {
+    let gen = gen!(|token: GeneratorToken<Result<u32, Error>, u32>| {
+        let first = token.try_argument().await;
+        token.yield_(first).await;
+
+        let second = token.try_argument().await;
+        token.yield_(second).await;
+    });
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(5), GeneratorState::Yielded(Ok(5)));
+    assert_eq!(
+        gen.as_mut().resume(6),
+        GeneratorState::Yielded(Err(Error::NoArgument))
+    );
+}
+
+#[test]
+fn token_used_in_another_generator() {
+    let gen = gen!(|outer: GeneratorToken<Result<(), Error>>| {
+        let outer = &outer;
+        let inner = gen!(|_inner: GeneratorToken<u32>| {
+            let result = outer.try_yield_(Ok(())).await;
+            assert_eq!(result, Err(Error::TokenMismatch));
+        });
+        let mut inner = std::pin::pin!(inner);
+
+        let state = inner.as_mut().resume(());
+        assert_eq!(state, GeneratorState::Complete(()));
+        outer.yield_(Err(Error::TokenMismatch)).await;
+    });
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(
+        gen.as_mut().resume(()),
+        GeneratorState::Yielded(Err(Error::TokenMismatch))
+    );
+}
+
+#[test]
+fn try_resume_after_completion() {
+    let gen = gen!(|_token: GeneratorToken<u32>| {});
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(
+        gen.as_mut().try_resume(()),
+        Ok(GeneratorState::Complete(()))
+    );
+    assert_eq!(gen.as_mut().try_resume(()), Err(Error::Completed));
+}
+
+#[test]
+fn try_resume_pending_without_yield() {
+    let gen = gen!(|_token: GeneratorToken<u32>| {
+        tokio::task::yield_now().await;
+    });
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(gen.as_mut().try_resume(()), Err(Error::PendingWithoutYield));
+    assert_eq!(gen.as_mut().try_resume(()), Err(Error::Poisoned));
+}
+
+#[tokio::test]
+async fn async_try_resume_after_completion() {
+    let gen = gen!(async |token: GeneratorToken<u32>| {
+        token.yield_(1).await;
+    });
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(
+        gen.as_mut().try_resume(()).await,
+        Ok(GeneratorState::Yielded(1))
+    );
+    assert_eq!(
+        gen.as_mut().try_resume(()).await,
+        Ok(GeneratorState::Complete(()))
+    );
+    assert_eq!(gen.as_mut().try_resume(()).await, Err(Error::Completed));
+}
+
+#[test]
+#[should_panic = "token belongs to `gen!` at tests/error.rs:87:15, but was used within `gen!` at tests/error.rs:89:21"]
+fn token_mismatch_names_both_generators() {
+    let gen = gen!(|outer: GeneratorToken<u32>| {
+        let outer = &outer;
+        let inner = gen!(|_inner: GeneratorToken<u32>| {
+            outer.yield_(1).await;
+        });
+
+        let _ = std::pin::pin!(inner).resume(());
+    });
+
+    let _ = std::pin::pin!(gen).resume(());
+}
+
+#[test]
+fn token_outlives_its_generator() {
+    let stash = std::cell::Cell::new(None);
+    let stash = &stash;
+
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        stash.set(Some(token));
+    });
+    assert_eq!(std::pin::pin!(gen).resume(()), GeneratorState::Complete(()));
+
+    // The token can't be mistaken for the token of a generator created later,
+    // even one that reuses the same memory.
+    let stale = stash.take().unwrap();
+    let stale = &stale;
+    for _ in 0..10 {
+        let gen = gen!(|_token: GeneratorToken<u32>| {
+            assert_eq!(stale.try_yield_(1).await, Err(Error::TokenMismatch));
+        });
+        assert_eq!(std::pin::pin!(gen).resume(()), GeneratorState::Complete(()));
+    }
+}
@@ -0,0 +1,102 @@
+use std::pin::pin;
+
+use fauxgen::{gen_block, Generator, GeneratorState};
+
+#[test]
+fn yields_values() {
+    let count = 3;
+    let gen = gen_block! { yield = u32;
+        for i in 0..count {
+            r#yield!(i);
+        }
+    };
+
+    assert_eq!(pin!(gen).collect::<Vec<_>>(), [0, 1, 2]);
+}
+
+#[test]
+fn no_parameters() {
+    let gen = gen_block! { ;
+        r#yield!();
+        r#yield!();
+    };
+
+    assert_eq!(pin!(gen).count(), 2);
+}
+
+#[test]
+fn argument_and_return() {
+    let gen = gen_block! { move yield = &'static str, arg = u32 -> String;
+        let first = argument!();
+        let second = r#yield!("first");
+        format!("{first} {second}")
+    };
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded("first"));
+    assert_eq!(
+        gen.as_mut().resume(2),
+        GeneratorState::Complete("1 2".into())
+    );
+}
+
+#[test]
+fn early_return() {
+    let gen = gen_block! { yield = u32, arg = bool -> Option<u32>;
+        if r#yield!(1) {
+            return None;
+        }
+        Some(2)
+    };
+    let mut gen = pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(false), GeneratorState::Yielded(1));
+    assert_eq!(gen.as_mut().resume(true), GeneratorState::Complete(None));
+}
+
+#[test]
+fn size_hint_and_batches() {
+    let gen = gen_block! { yield = u32, size_hint = (4, Some(4));
+        yield_batch!([1, 2]);
+        size_hint!(2, Some(2));
+        yield_batch!([3, 4]);
+    };
+    let gen = pin!(gen);
+
+    assert_eq!(gen.size_hint(), (4, Some(4)));
+    assert_eq!(gen.sum::<u32>(), 10);
+}
+
+#[test]
+fn fn_pointer_yield_type() {
+    fn one() -> u32 {
+        1
+    }
+
+    // The `->` within the yield type is not mistaken for the return type.
+    let gen = gen_block! { yield = fn() -> u32 -> u32;
+        r#yield!(one);
+        2
+    };
+    let mut gen = pin!(gen);
+
+    match gen.as_mut().resume(()) {
+        GeneratorState::Yielded(func) => assert_eq!(func(), 1),
+        GeneratorState::Complete(_) => panic!("generator completed early"),
+    }
+    assert_eq!(gen.as_mut().resume(()), GeneratorState::Complete(2));
+}
+
+#[tokio::test]
+async fn async_block() {
+    use futures_util::StreamExt;
+
+    let gen = gen_block! { async yield = u32;
+        for i in 0..3 {
+            tokio::task::yield_now().await;
+            r#yield!(i);
+        }
+    };
+
+    assert_eq!(gen.collect::<Vec<_>>().await, [0, 1, 2]);
+}
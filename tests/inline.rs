@@ -14,6 +14,19 @@ fn basic_sync() {
     assert_eq!(vals, [32, 5]);
 }
 
+#[test]
+fn move_closure() {
+    let values = vec![1, 2, 3];
+    let gen = std::pin::pin!(gen!(move |token: GeneratorToken<_>| {
+        for value in values {
+            token.yield_(value).await;
+        }
+    }));
+
+    let vals: Vec<_> = gen.collect();
+    assert_eq!(vals, [1, 2, 3]);
+}
+
 #[tokio::test]
 async fn basic_async() {
     let mut gen = std::pin::pin!(gen!(async |token: GeneratorToken<_>| {
@@ -25,3 +38,17 @@ async fn basic_async() {
         assert_eq!(item, 888);
     }
 }
+
+#[test]
+fn first_argument() {
+    use fauxgen::{Generator, GeneratorState};
+
+    let gen = gen!(|token, first: u32| {
+        let second = token.yield_(first * 10).await;
+        first + second
+    });
+    let mut gen = std::pin::pin!(gen);
+
+    assert_eq!(gen.as_mut().resume(1), GeneratorState::Yielded(10));
+    assert_eq!(gen.as_mut().resume(2), GeneratorState::Complete(3));
+}
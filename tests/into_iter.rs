@@ -0,0 +1,158 @@
+use fauxgen::{BoxedIter, BoxedSendStream, BoxedStream};
+
+struct Node {
+    value: u32,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(value: u32, children: Vec<Node>) -> Self {
+        Self { value, children }
+    }
+}
+
+struct Tree {
+    root: Node,
+}
+
+impl Tree {
+    fn sample() -> Self {
+        Self {
+            root: Node::new(
+                1,
+                vec![
+                    Node::new(2, vec![Node::new(3, vec![])]),
+                    Node::new(4, vec![]),
+                ],
+            ),
+        }
+    }
+}
+
+#[fauxgen::into_iter(yield = &'a Node)]
+impl<'a> IntoIterator for &'a Tree {
+    fn into_iter(self) {
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            r#yield!(node);
+            stack.extend(node.children.iter().rev());
+        }
+    }
+}
+
+struct Countdown(u32);
+
+#[fauxgen::into_iter(yield = u32, size_hint = (self.0 as usize, Some(self.0 as usize)))]
+impl IntoIterator for Countdown {
+    fn into_iter(self) {
+        for i in (1..=self.0).rev() {
+            r#yield!(i);
+        }
+    }
+}
+
+#[fauxgen::into_iter(yield = u32)]
+impl Tree {
+    fn values(&self) {
+        for node in self {
+            r#yield!(node.value);
+        }
+    }
+}
+
+#[fauxgen::into_stream(yield = u32)]
+impl Tree {
+    async fn values_stream(&self) {
+        for node in self {
+            tokio::task::yield_now().await;
+            r#yield!(node.value);
+        }
+    }
+}
+
+#[fauxgen::into_stream(yield = u32, send)]
+impl Tree {
+    async fn values_send(&self) {
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            tokio::task::yield_now().await;
+            r#yield!(node.value);
+            stack.extend(node.children.iter().rev());
+        }
+    }
+}
+
+trait IntoStream {
+    type Item;
+    type IntoStream: futures_core::Stream<Item = Self::Item>;
+
+    fn into_stream(self) -> Self::IntoStream;
+}
+
+#[fauxgen::into_stream(yield = &'a Node)]
+impl<'a> IntoStream for &'a Tree {
+    fn into_stream(self) {
+        for node in self {
+            r#yield!(node);
+        }
+    }
+}
+
+#[test]
+fn trait_impl() {
+    let tree = Tree::sample();
+    let values: Vec<_> = (&tree).into_iter().map(|node| node.value).collect();
+
+    assert_eq!(values, [1, 2, 3, 4]);
+}
+
+#[test]
+fn owned_impl() {
+    let iter = Countdown(3).into_iter();
+
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.collect::<Vec<_>>(), [3, 2, 1]);
+}
+
+#[test]
+fn inherent_impl() {
+    let tree = Tree::sample();
+    let values: BoxedIter<'_, u32> = tree.values();
+
+    assert_eq!(values.sum::<u32>(), 10);
+}
+
+#[tokio::test]
+async fn streams() {
+    use futures_util::StreamExt;
+
+    let tree = Tree::sample();
+    let values: BoxedStream<'_, u32> = tree.values_stream();
+    assert_eq!(values.collect::<Vec<_>>().await, [1, 2, 3, 4]);
+
+    let nodes = (&tree).into_stream().map(|node| node.children.len());
+    assert_eq!(nodes.collect::<Vec<_>>().await, [2, 1, 0, 0]);
+}
+
+#[test]
+fn send_stream() {
+    use futures_util::StreamExt;
+
+    let tree = Tree::sample();
+    let values: BoxedSendStream<'_, u32> = tree.values_send();
+
+    // The stream is created on this thread and consumed on another one.
+    let values = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                runtime.block_on(values.collect::<Vec<_>>())
+            })
+            .join()
+            .unwrap()
+    });
+
+    assert_eq!(values, [1, 2, 3, 4]);
+}
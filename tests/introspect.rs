@@ -0,0 +1,134 @@
+use std::pin::pin;
+
+use fauxgen::{Generator, GeneratorState};
+
+#[fauxgen::generator(yield = u32, introspect)]
+fn sites(repeat: usize) {
+    for _ in 0..repeat {
+        r#yield!(1);
+    }
+
+    yield_batch!([2, 3]);
+    r#yield!(4);
+}
+
+#[test]
+fn current_yield_site() {
+    let mut gen = pin!(sites(2));
+    assert_eq!(gen.yield_sites().len(), 3);
+    assert_eq!(gen.current_yield_site().map(|site| site.index()), None);
+
+    let mut resume = || {
+        let state = gen.as_mut().resume(());
+        let site = gen.current_yield_site().map(|site| site.index());
+        (state, site)
+    };
+
+    assert_eq!(resume(), (GeneratorState::Yielded(1), Some(0)));
+    assert_eq!(resume(), (GeneratorState::Yielded(1), Some(0)));
+    assert_eq!(resume(), (GeneratorState::Yielded(2), Some(1)));
+    assert_eq!(resume(), (GeneratorState::Yielded(3), Some(1)));
+    assert_eq!(resume(), (GeneratorState::Yielded(4), Some(2)));
+    assert_eq!(resume(), (GeneratorState::Complete(()), None));
+}
+
+#[test]
+fn site_locations() {
+    let gen = sites(0);
+    let sites = gen.yield_sites();
+
+    assert!(sites.iter().all(|site| site.file() == file!()));
+    assert_eq!(sites[0].line(), 8);
+    assert_eq!(sites[0].column(), 9);
+    assert_eq!(sites[1].line(), 11);
+    assert_eq!(sites[2].to_string(), format!("{}:12:5", file!()));
+
+    // Without counters there is nothing to report.
+    assert_eq!(sites[0].resumes(), None);
+    assert_eq!(sites[0].time_suspended(), None);
+}
+
+#[test]
+fn debug_includes_site() {
+    let mut gen = pin!(sites(1));
+    assert!(!format!("{gen:?}").contains("yield_site"));
+
+    gen.as_mut().resume(());
+    let site = format!("yield_site: {}:8:9", file!());
+    assert!(format!("{gen:?}").contains(&site));
+}
+
+#[fauxgen::generator(yield = u32, introspect(counters))]
+fn counted() {
+    r#yield!(1);
+    for i in 0..3 {
+        r#yield!(i);
+    }
+}
+
+#[test]
+fn counters() {
+    let gen = counted();
+    let sites = gen.yield_sites();
+    assert_eq!(sites.len(), 2);
+
+    assert_eq!(pin!(gen).count(), 4);
+    assert_eq!(pin!(counted()).count(), 4);
+
+    // The counters are shared by every instance of the generator.
+    assert_eq!(sites[0].resumes(), Some(2));
+    assert_eq!(sites[1].resumes(), Some(6));
+    assert!(sites[1].time_suspended().is_some());
+}
+
+#[fauxgen::generator(yield = T, introspect(counters))]
+fn repeated<T: Clone>(value: T, count: usize) {
+    for _ in 0..count {
+        r#yield!(value.clone());
+    }
+}
+
+#[test]
+fn generic_counters() {
+    let sites = repeated(0u32, 0).yield_sites();
+
+    assert_eq!(pin!(repeated(1u32, 2)).count(), 2);
+    assert_eq!(pin!(repeated("a", 3)).count(), 3);
+
+    // Every instantiation of a generic generator shares the same table.
+    assert!(std::ptr::eq(sites, repeated("b", 0).yield_sites()));
+    assert_eq!(sites[0].resumes(), Some(5));
+}
+
+#[test]
+fn not_introspected() {
+    #[fauxgen::generator(yield = u32)]
+    fn plain() {
+        r#yield!(1);
+    }
+
+    let mut gen = pin!(plain());
+    gen.as_mut().resume(());
+
+    assert!(gen.yield_sites().is_empty());
+    assert!(gen.current_yield_site().is_none());
+}
+
+#[tokio::test]
+async fn inline_and_async() {
+    use futures_util::StreamExt;
+
+    let gen = fauxgen::gen_block! { async yield = u32, introspect;
+        tokio::task::yield_now().await;
+        r#yield!(vec![yield 1, yield 2].len() as u32);
+    };
+    let mut gen = pin!(gen);
+
+    // Sites are numbered in the order they appear in the source.
+    assert_eq!(gen.next().await, Some(1));
+    assert_eq!(gen.current_yield_site().map(|site| site.index()), Some(1));
+    assert_eq!(gen.next().await, Some(2));
+    assert_eq!(gen.current_yield_site().map(|site| site.index()), Some(2));
+    assert_eq!(gen.next().await, Some(2));
+    assert_eq!(gen.current_yield_site().map(|site| site.index()), Some(0));
+}
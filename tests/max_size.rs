@@ -0,0 +1,110 @@
+use std::mem::size_of_val;
+
+#[fauxgen::generator(yield = u8, max_size = 256)]
+fn small() {
+    for byte in [1u8, 2, 3] {
+        r#yield!(byte);
+    }
+}
+
+#[fauxgen::generator(yield = u8, max_size = 256, boxed_state)]
+fn large() {
+    let buffer = [7u8; 4096];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+#[fauxgen::generator(yield = u8, max_size = 256, boxed_state)]
+fn small_boxed() {
+    let buffer = [7u8; 16];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+#[fauxgen::generator(yield = u8, boxed_state)]
+fn large_unbounded() {
+    let buffer = [7u8; 4096];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+#[fauxgen::generator(yield = u8, boxed_state)]
+async fn large_async() {
+    let buffer = [7u8; 4096];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+#[test]
+fn within_max_size() {
+    assert_eq!(std::pin::pin!(small()).sum::<u8>(), 6);
+}
+
+#[test]
+fn boxed_state() {
+    let gen = large();
+    assert!(size_of_val(&gen) <= 256);
+
+    let gen = std::pin::pin!(gen);
+    assert_eq!(gen.map(u32::from).sum::<u32>(), 7 * 4096);
+}
+
+#[test]
+fn boxed_state_within_max_size() {
+    let gen = small_boxed();
+    assert!(size_of_val(&gen) <= 256);
+
+    let gen = std::pin::pin!(gen);
+    assert_eq!(gen.map(u32::from).sum::<u32>(), 7 * 16);
+}
+
+#[tokio::test]
+async fn boxed_state_async() {
+    use futures_util::StreamExt;
+
+    let gen = large_async();
+    assert!(size_of_val(&gen) < 4096);
+    assert_eq!(gen.count().await, 4096);
+}
+
+#[test]
+fn inline_boxed_state() {
+    let gen = fauxgen::gen_block! { yield = u8, max_size = 256, boxed_state;
+        let buffer = [7u8; 4096];
+        for byte in buffer {
+            r#yield!(byte);
+        }
+    };
+
+    assert!(size_of_val(&gen) <= 256);
+    assert_eq!(std::pin::pin!(gen).count(), 4096);
+}
+
+#[fauxgen::generator(yield = u8, boxed_state)]
+fn batches() {
+    yield_batch!([1u8, 2, 3]);
+    r#yield!(4);
+}
+
+#[test]
+fn boxed_state_moved_between_resumes() {
+    // Without a `max_size` the state is always boxed, so the generator doesn't
+    // need to be pinned.
+    let mut gen = large_unbounded();
+    assert_eq!(gen.next(), Some(7));
+
+    let mut gen = Box::new(gen);
+    assert_eq!(gen.next(), Some(7));
+    assert_eq!(gen.count(), 4094);
+
+    // Moved part way through a batch.
+    let mut gen = batches();
+    assert_eq!(gen.next(), Some(1));
+
+    let gen = vec![gen];
+    assert_eq!(gen.into_iter().flatten().collect::<Vec<_>>(), [2, 3, 4]);
+}
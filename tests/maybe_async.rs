@@ -0,0 +1,89 @@
+use std::pin::pin;
+
+use futures_util::StreamExt;
+
+fn double(value: u32) -> u32 {
+    value * 2
+}
+
+async fn double_async(value: u32) -> u32 {
+    tokio::task::yield_now().await;
+    value * 2
+}
+
+#[fauxgen::generator(yield = u32, maybe_async)]
+async fn doubled(count: u32) {
+    for i in 0..count {
+        r#yield!(double_async(i).await);
+    }
+}
+
+#[test]
+fn sync_version() {
+    assert_eq!(pin!(doubled(3)).collect::<Vec<_>>(), [0, 2, 4]);
+}
+
+#[tokio::test]
+async fn async_version() {
+    assert_eq!(doubled_async(3).collect::<Vec<_>>().await, [0, 2, 4]);
+}
+
+struct Source(Vec<&'static str>);
+
+impl Source {
+    fn next_word(&mut self) -> Option<&'static str> {
+        self.0.pop()
+    }
+
+    async fn next_word_async(&mut self) -> Option<&'static str> {
+        tokio::task::yield_now().await;
+        self.0.pop()
+    }
+}
+
+#[fauxgen::generator(yield = String, maybe_async)]
+fn words(mut source: Source) {
+    while let Some(word) = source.next_word_async().await {
+        r#yield!(format!("{word}: {}", double_async(word.len() as u32).await));
+    }
+}
+
+#[tokio::test]
+async fn methods_and_macros() {
+    let source = || Source(vec!["bc", "a"]);
+    let expected = ["a: 2", "bc: 4"];
+
+    assert_eq!(pin!(words(source())).collect::<Vec<_>>(), expected);
+    assert_eq!(words_async(source()).collect::<Vec<_>>().await, expected);
+}
+
+#[fauxgen::generator(yield = u32, arg = u32, maybe_async)]
+fn echo(#[arg] first: u32) -> u32 {
+    let second = r#yield!(first);
+    double_async(first + second).await
+}
+
+#[tokio::test]
+async fn arguments_and_return() {
+    use fauxgen::{AsyncGenerator, Generator, GeneratorState};
+
+    let mut gen = pin!(echo());
+    assert_eq!(
+        Generator::resume(gen.as_mut(), 1),
+        GeneratorState::Yielded(1)
+    );
+    assert_eq!(
+        Generator::resume(gen.as_mut(), 2),
+        GeneratorState::Complete(6)
+    );
+
+    let mut gen = pin!(echo_async());
+    assert_eq!(
+        AsyncGenerator::resume(gen.as_mut(), 1).await,
+        GeneratorState::Yielded(1)
+    );
+    assert_eq!(
+        AsyncGenerator::resume(gen.as_mut(), 2).await,
+        GeneratorState::Complete(6)
+    );
+}
@@ -0,0 +1,59 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use fauxgen::{AsyncGenerator, CatchUnwind, Generator, GeneratorState};
+
+#[fauxgen::generator(yield = u32)]
+fn fails_after(count: u32) {
+    for i in 0..count {
+        r#yield!(i);
+    }
+
+    panic!("generator failed");
+}
+
+#[fauxgen::generator(yield = u32)]
+async fn fails_async() {
+    r#yield!(1);
+    panic!("generator failed");
+}
+
+#[test]
+#[should_panic = "generator resumed after panicking"]
+fn poisoned_after_panic() {
+    let mut gen = std::pin::pin!(fails_after(0));
+
+    let result = catch_unwind(AssertUnwindSafe(|| gen.as_mut().resume(())));
+    assert!(result.is_err());
+
+    let _ = gen.as_mut().resume(());
+}
+
+#[test]
+fn catch_unwind_sync() {
+    let mut gen = std::pin::pin!(CatchUnwind::new(AssertUnwindSafe(fails_after(1))));
+
+    assert!(matches!(
+        gen.as_mut().resume(()),
+        GeneratorState::Yielded(0)
+    ));
+
+    let payload = match gen.as_mut().resume(()) {
+        GeneratorState::Complete(Err(payload)) => payload,
+        _ => panic!("generator did not panic"),
+    };
+    assert_eq!(payload.downcast_ref(), Some(&"generator failed"));
+}
+
+#[tokio::test]
+async fn catch_unwind_async() {
+    let mut gen = std::pin::pin!(CatchUnwind::new(AssertUnwindSafe(fails_async())));
+
+    assert!(matches!(
+        gen.as_mut().resume(()).await,
+        GeneratorState::Yielded(1)
+    ));
+    assert!(matches!(
+        gen.as_mut().resume(()).await,
+        GeneratorState::Complete(Err(_))
+    ));
+}
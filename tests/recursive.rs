@@ -0,0 +1,106 @@
+use fauxgen::{Generator, GeneratorState};
+
+struct Node {
+    value: u32,
+    children: Vec<Node>,
+}
+
+#[fauxgen::generator(yield = u32, recursive)]
+fn walk(node: &Node) -> u32 {
+    let mut count = 1;
+    r#yield!(node.value);
+
+    for child in &node.children {
+        count += recurse!(walk(child));
+    }
+
+    count
+}
+
+#[fauxgen::generator(yield = u32, recursive)]
+fn countdown(n: u32) {
+    if n == 0 {
+        return;
+    }
+
+    recurse!(countdown(n - 1));
+    r#yield!(n);
+}
+
+#[fauxgen::generator(yield = u32)]
+fn range(start: u32, end: u32) -> u32 {
+    for i in start..end {
+        r#yield!(i);
+    }
+
+    end - start
+}
+
+#[fauxgen::generator(yield = u32, recursive)]
+fn ranges(n: u32) -> u32 {
+    if n == 0 {
+        return 0;
+    }
+
+    let count = recurse!(ranges(n - 1));
+    count + yield_from_boxed!(range(n * 10, n * 10 + n))
+}
+
+#[test]
+fn tree_walk() {
+    let tree = Node {
+        value: 1,
+        children: vec![
+            Node {
+                value: 2,
+                children: vec![Node {
+                    value: 3,
+                    children: vec![],
+                }],
+            },
+            Node {
+                value: 4,
+                children: vec![],
+            },
+        ],
+    };
+
+    let mut gen = walk(&tree);
+    let mut values = Vec::new();
+
+    let count = loop {
+        match std::pin::Pin::new(&mut gen).resume(()) {
+            GeneratorState::Yielded(value) => values.push(value),
+            GeneratorState::Complete(count) => break count,
+        }
+    };
+
+    assert_eq!(values, [1, 2, 3, 4]);
+    assert_eq!(count, 4);
+    assert_eq!(gen.depth(), 0);
+}
+
+#[test]
+fn deep_recursion() {
+    let values: Vec<_> = countdown(100_000).collect();
+
+    assert_eq!(values.len(), 100_000);
+    assert_eq!(values[0], 1);
+    assert_eq!(values[99_999], 100_000);
+}
+
+#[test]
+fn yield_from_boxed() {
+    let mut gen = ranges(3);
+    let mut values = Vec::new();
+
+    let count = loop {
+        match std::pin::Pin::new(&mut gen).resume(()) {
+            GeneratorState::Yielded(value) => values.push(value),
+            GeneratorState::Complete(count) => break count,
+        }
+    };
+
+    assert_eq!(values, [10, 20, 21, 30, 31, 32]);
+    assert_eq!(count, 6);
+}
@@ -0,0 +1,81 @@
+use std::pin::Pin;
+
+use fauxgen::{Generator, GeneratorState, Replayable};
+
+#[fauxgen::generator(yield = Vec<u8>, arg = u8)]
+fn path() -> usize {
+    let mut path = vec![argument!()];
+
+    while path.len() < 3 {
+        path.push(r#yield!(path.clone()));
+    }
+
+    path.len()
+}
+
+#[test]
+fn fork_explores_both_branches() {
+    let mut gen = Replayable::new(path);
+    let resume = |gen: &mut Replayable<_, _, u8>, arg| Pin::new(gen).resume(arg);
+
+    assert_eq!(resume(&mut gen, 1), GeneratorState::Yielded(vec![1]));
+
+    let mut left = gen.fork();
+    let mut right = gen.fork();
+
+    assert_eq!(resume(&mut left, 2), GeneratorState::Yielded(vec![1, 2]));
+    assert_eq!(resume(&mut right, 3), GeneratorState::Yielded(vec![1, 3]));
+    assert_eq!(right.history(), [1, 3]);
+
+    let mut deeper = right.fork();
+    assert_eq!(resume(&mut deeper, 4), GeneratorState::Complete(3));
+    assert_eq!(resume(&mut left, 5), GeneratorState::Complete(3));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "replayed generator diverged"]
+fn fork_detects_nondeterminism() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    #[fauxgen::generator(yield = u32)]
+    fn nondeterministic() {
+        loop {
+            r#yield!(COUNTER.fetch_add(1, Ordering::Relaxed));
+        }
+    }
+
+    let mut gen = Replayable::new(nondeterministic);
+    let _ = Pin::new(&mut gen).resume(());
+    let _ = gen.fork();
+}
+
+#[test]
+fn fork_after_panic() {
+    #[fauxgen::generator(yield = u8, arg = u8)]
+    fn nonzero() {
+        let mut value = argument!();
+
+        loop {
+            assert_ne!(value, 0, "zero is not allowed");
+            value = r#yield!(value);
+        }
+    }
+
+    let mut gen = Replayable::new(nonzero);
+    assert_eq!(Pin::new(&mut gen).resume(1), GeneratorState::Yielded(1));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        Pin::new(&mut gen).resume(0)
+    }));
+    assert!(result.is_err());
+
+    // The argument that caused the panic isn't part of the history, so the fork
+    // picks up from before it.
+    assert_eq!(gen.history(), [1]);
+
+    let mut fork = gen.fork();
+    assert_eq!(Pin::new(&mut fork).resume(2), GeneratorState::Yielded(2));
+}
@@ -0,0 +1,136 @@
+use std::pin::pin;
+
+use fauxgen::{gen, GeneratorIter, GeneratorToken, GeneratorTryStream, LagPolicy, YieldHint};
+use futures_util::{Stream, StreamExt};
+
+#[fauxgen::generator(yield = usize, size_hint = (count, Some(count)))]
+fn exact(count: usize) {
+    for i in 0..count {
+        r#yield!(i);
+    }
+}
+
+#[fauxgen::generator(yield = usize)]
+fn updates(count: usize) {
+    r#yield!(0);
+    size_hint!(count, None);
+
+    for i in 0..count {
+        r#yield!(i);
+    }
+}
+
+#[fauxgen::generator(yield = usize, size_hint = (count, Some(count)))]
+async fn exact_async(count: usize) {
+    for i in 0..count {
+        r#yield!(i);
+    }
+}
+
+#[fauxgen::generator(yield = usize, size_hint = (count, Some(count)))]
+async fn fallible(count: usize) -> Result<(), &'static str> {
+    for i in 0..count {
+        r#yield!(i);
+    }
+
+    Err("done")
+}
+
+#[test]
+fn initial_hint() {
+    let mut gen = pin!(exact(3));
+    assert_eq!(gen.size_hint(), (3, Some(3)));
+
+    gen.next();
+    assert_eq!(gen.size_hint(), (2, Some(2)));
+
+    assert_eq!(gen.as_mut().count(), 2);
+    assert_eq!(gen.size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn collect_preallocates() {
+    let values: Vec<_> = Box::pin(exact(100)).collect();
+    assert_eq!(values.len(), 100);
+    assert_eq!(values.capacity(), 100);
+}
+
+#[test]
+fn updated_hint() {
+    let mut gen = pin!(updates(4));
+    assert_eq!(gen.size_hint(), (0, None));
+
+    gen.next();
+    assert_eq!(gen.size_hint(), (0, None));
+
+    gen.next();
+    assert_eq!(gen.size_hint(), (3, None));
+}
+
+#[test]
+fn inline_hint() {
+    let gen = gen!(|token: GeneratorToken<_>| {
+        token.set_size_hint(2, Some(2)).await;
+        token.yield_(1).await;
+        token.yield_(2).await;
+    });
+    let mut gen = pin!(gen);
+
+    gen.next();
+    assert_eq!(gen.size_hint(), (1, Some(1)));
+}
+
+#[test]
+fn generator_iter_hint() {
+    let gen = Box::pin(exact(5));
+    assert_eq!(gen.yield_hint(), (5, Some(5)));
+
+    let mut iter = GeneratorIter::new(gen);
+    assert_eq!(iter.size_hint(), (5, Some(5)));
+
+    iter.next();
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+}
+
+#[test]
+fn stream_hints() {
+    let stream = exact_async(2);
+    assert_eq!(Stream::size_hint(&stream), (2, Some(2)));
+
+    // The error returned at the end of the generator is one more item.
+    let stream = GeneratorTryStream::new(fallible(2));
+    assert_eq!(stream.size_hint(), (2, Some(3)));
+}
+
+#[test]
+fn tee_hints() {
+    let mut branches = fauxgen::tee(exact(3), 2);
+    let second = branches.pop().unwrap();
+    let mut first = branches.pop().unwrap();
+
+    assert_eq!(first.next(), Some(0));
+    assert_eq!(first.yield_hint(), (2, Some(2)));
+
+    // The value read by the first branch is still buffered for the second.
+    assert_eq!(second.yield_hint(), (3, Some(3)));
+}
+
+#[tokio::test]
+async fn share_hints() {
+    let mut branches = fauxgen::share(exact_async(3), 2, 4, LagPolicy::Block);
+    let second = GeneratorTryStream::new(branches.pop().unwrap());
+    let mut first = GeneratorTryStream::new(branches.pop().unwrap());
+
+    // Each branch may end with an error if it lags, which is one more item.
+    assert_eq!(first.next().await, Some(Ok(0)));
+    assert_eq!(first.size_hint(), (2, Some(3)));
+    assert_eq!(second.size_hint(), (3, Some(4)));
+
+    // Buffered values may be dropped before a slow branch reads them.
+    let mut branches = fauxgen::share(exact_async(3), 2, 4, LagPolicy::DropOldest);
+    let second = GeneratorTryStream::new(branches.pop().unwrap());
+    let mut first = GeneratorTryStream::new(branches.pop().unwrap());
+
+    assert_eq!(first.next().await, Some(Ok(0)));
+    assert_eq!(second.size_hint(), (0, Some(4)));
+}
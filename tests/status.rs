@@ -0,0 +1,143 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use fauxgen::{AsyncGenerator, Generator, GeneratorState, GeneratorStatus};
+
+#[fauxgen::generator(yield = u32)]
+fn one() {
+    r#yield!(1);
+}
+
+#[fauxgen::generator(yield = u32)]
+async fn waits() {
+    tokio::task::yield_now().await;
+    r#yield!(1);
+}
+
+#[test]
+fn sync_status() {
+    let mut gen = std::pin::pin!(one());
+    assert_eq!(gen.status(), GeneratorStatus::NotStarted);
+
+    let _ = gen.as_mut().resume(());
+    assert_eq!(gen.status(), GeneratorStatus::Suspended);
+
+    let _ = gen.as_mut().resume(());
+    assert_eq!(gen.status(), GeneratorStatus::Complete);
+}
+
+#[test]
+#[should_panic = "generator resumed after completion"]
+fn resume_after_completion() {
+    let mut gen = std::pin::pin!(one());
+    let _ = gen.as_mut().resume(());
+    let _ = gen.as_mut().resume(());
+    let _ = gen.as_mut().resume(());
+}
+
+#[test]
+fn async_status() {
+    let mut gen = std::pin::pin!(waits());
+    let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+
+    assert!(gen.as_mut().poll_resume(&mut cx, Some(())).is_pending());
+    assert_eq!(gen.status(), GeneratorStatus::Running);
+
+    assert!(matches!(
+        gen.as_mut().poll_resume(&mut cx, None),
+        Poll::Ready(_)
+    ));
+    assert_eq!(gen.status(), GeneratorStatus::Suspended);
+}
+
+#[fauxgen::generator(yield = Option<GeneratorStatus>)]
+fn observe() {
+    r#yield!(fauxgen::__private::current_status());
+    r#yield!(fauxgen::__private::current_status());
+}
+
+#[fauxgen::generator(yield = Option<GeneratorStatus>)]
+async fn observe_async() {
+    tokio::task::yield_now().await;
+    r#yield!(fauxgen::__private::current_status());
+}
+
+#[test]
+fn running_within_body() {
+    let mut gen = std::pin::pin!(observe());
+    assert_eq!(
+        gen.as_mut().resume(()),
+        GeneratorState::Yielded(Some(GeneratorStatus::Running))
+    );
+
+    // Iterating goes through a different path from resuming.
+    let statuses: Vec<_> = gen.chain(std::pin::pin!(observe())).collect();
+    assert_eq!(statuses, [Some(GeneratorStatus::Running); 3]);
+
+    assert_eq!(fauxgen::__private::current_status(), None);
+}
+
+#[tokio::test]
+async fn running_within_async_body() {
+    use futures_util::StreamExt;
+
+    let statuses: Vec<_> = observe_async().collect().await;
+    assert_eq!(statuses, [Some(GeneratorStatus::Running)]);
+}
+
+#[test]
+fn poisoned_status() {
+    #[fauxgen::generator(yield = u32)]
+    fn fails() {
+        panic!("generator failed");
+    }
+
+    let mut gen = Box::pin(fails());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        Pin::as_mut(&mut gen).resume(())
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(gen.status(), GeneratorStatus::Poisoned);
+}
+
+#[test]
+fn debug_output() {
+    let gen = one();
+    let debug = format!("{gen:?}");
+
+    assert!(debug.contains("SyncGenerator"), "{debug}");
+    assert!(debug.contains("NotStarted"), "{debug}");
+    assert!(debug.contains("u32"), "{debug}");
+}
+
+#[test]
+fn debug_includes_name() {
+    let gen = one();
+    let debug = format!("{gen:?}");
+
+    assert!(debug.contains("status::one"), "{debug}");
+    assert!(debug.contains("tests/status.rs:"), "{debug}");
+}
+
+#[test]
+#[should_panic = "(in `status::one` at tests/status.rs:7:4)"]
+fn panic_includes_name() {
+    let mut gen = std::pin::pin!(one());
+    let _ = gen.as_mut().resume(());
+    let _ = gen.as_mut().resume(());
+    let _ = gen.as_mut().resume(());
+}
+
+#[test]
+fn gen_macro_debug() {
+    use fauxgen::{gen, GeneratorToken};
+
+    let gen = gen!(|token: GeneratorToken<u32>| {
+        token.yield_(1).await;
+    });
+    let debug = format!("{gen:?}");
+
+    assert!(debug.contains("gen!"), "{debug}");
+    assert!(debug.contains("tests/status.rs:"), "{debug}");
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use fauxgen::{AsyncGenerator, GeneratorState, GeneratorTryStream, LagPolicy, Lagged};
+use futures_util::StreamExt;
+
+#[fauxgen::generator(yield = u32)]
+fn numbers(count: u32) -> &'static str {
+    for i in 0..count {
+        r#yield!(i);
+    }
+
+    "done"
+}
+
+#[fauxgen::generator(yield = u32)]
+async fn slow_numbers(count: u32) {
+    for i in 0..count {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        r#yield!(i);
+    }
+}
+
+#[test]
+fn tee_interleaved() {
+    use fauxgen::Generator;
+
+    let mut branches = fauxgen::tee(numbers(3), 2);
+    let mut second = branches.pop().unwrap();
+    let mut first = branches.pop().unwrap();
+
+    let resume = |gen: &mut fauxgen::Tee<_>| std::pin::Pin::new(gen).resume(());
+
+    assert_eq!(resume(&mut first), GeneratorState::Yielded(0));
+    assert_eq!(resume(&mut first), GeneratorState::Yielded(1));
+    assert_eq!(resume(&mut second), GeneratorState::Yielded(0));
+    assert_eq!(resume(&mut first), GeneratorState::Yielded(2));
+    assert_eq!(resume(&mut first), GeneratorState::Complete("done"));
+    assert_eq!(resume(&mut second), GeneratorState::Yielded(1));
+    assert_eq!(resume(&mut second), GeneratorState::Yielded(2));
+    assert_eq!(resume(&mut second), GeneratorState::Complete("done"));
+}
+
+#[tokio::test]
+async fn share_concurrent() {
+    let branches = fauxgen::share(slow_numbers(50), 3, 4, LagPolicy::Block);
+    let tasks: Vec<_> = branches
+        .into_iter()
+        .map(|branch| tokio::spawn(GeneratorTryStream::new(branch).collect::<Vec<_>>()))
+        .collect();
+
+    let expected: Vec<Result<u32, Lagged>> = (0..50).map(Ok).collect();
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn share_lagged_error() {
+    let mut branches = fauxgen::share(slow_numbers(10), 2, 2, LagPolicy::Error);
+    let mut slow = branches.pop().unwrap();
+    let fast = branches.pop().unwrap();
+
+    let fast: Vec<_> = GeneratorTryStream::new(fast).collect().await;
+    assert_eq!(fast.len(), 10);
+
+    let state = std::pin::Pin::new(&mut slow).resume(()).await;
+    assert_eq!(state, GeneratorState::Complete(Err(Lagged { skipped: 8 })));
+}
+
+#[tokio::test]
+async fn share_drop_oldest() {
+    let mut branches = fauxgen::share(slow_numbers(10), 2, 3, LagPolicy::DropOldest);
+    let mut slow = branches.pop().unwrap();
+    let fast = branches.pop().unwrap();
+
+    let state = std::pin::Pin::new(&mut slow).resume(()).await;
+    assert_eq!(state, GeneratorState::Yielded(0));
+
+    let fast: Vec<_> = GeneratorTryStream::new(fast).collect().await;
+    assert_eq!(fast, (0..10).map(Ok).collect::<Vec<_>>());
+
+    // Only the newest values fit in the buffer, so the slow branch silently skips
+    // ahead to them.
+    let slow: Vec<_> = GeneratorTryStream::new(slow).collect().await;
+    assert_eq!(slow, [Ok(7), Ok(8), Ok(9)]);
+}
+
+#[tokio::test]
+async fn share_lagged_error_after_reading() {
+    let mut branches = fauxgen::share(slow_numbers(10), 2, 3, LagPolicy::Error);
+    let mut slow = branches.pop().unwrap();
+    let fast = branches.pop().unwrap();
+
+    let state = std::pin::Pin::new(&mut slow).resume(()).await;
+    assert_eq!(state, GeneratorState::Yielded(0));
+
+    let fast: Vec<_> = GeneratorTryStream::new(fast).collect().await;
+    assert_eq!(fast.len(), 10);
+
+    // Values 1 through 6 were dropped before the slow branch could see them.
+    let slow: Vec<_> = GeneratorTryStream::new(slow).collect().await;
+    assert_eq!(slow, [Err(Lagged { skipped: 6 })]);
+    assert_eq!(
+        slow[0].as_ref().unwrap_err().to_string(),
+        "shared generator consumer lagged behind by 6 values"
+    );
+}
+
+#[tokio::test]
+async fn share_lagged_error_per_branch() {
+    let mut branches = fauxgen::share(slow_numbers(6), 3, 2, LagPolicy::Error);
+    let lagging = branches.pop().unwrap();
+    let mut second = branches.pop().unwrap();
+    let first = branches.pop().unwrap();
+
+    let state = std::pin::Pin::new(&mut second).resume(()).await;
+    assert_eq!(state, GeneratorState::Yielded(0));
+
+    let first: Vec<_> = GeneratorTryStream::new(first).collect().await;
+    assert_eq!(first, (0..6).map(Ok).collect::<Vec<_>>());
+
+    // Each lagging branch reports the number of values that it missed.
+    let lagging: Vec<_> = GeneratorTryStream::new(lagging).collect().await;
+    assert_eq!(lagging, [Err(Lagged { skipped: 4 })]);
+
+    let second: Vec<_> = GeneratorTryStream::new(second).collect().await;
+    assert_eq!(second, [Err(Lagged { skipped: 3 })]);
+}
@@ -0,0 +1,131 @@
+use std::pin::pin;
+
+use fauxgen::{GeneratorTryIter, GeneratorTryStream};
+use futures_util::StreamExt;
+
+#[derive(Debug, PartialEq)]
+struct Overflow;
+
+fn check(value: u32) -> Result<u32, Overflow> {
+    match value < 3 {
+        true => Ok(value),
+        false => Err(Overflow),
+    }
+}
+
+#[fauxgen::generator(yield = u32, try)]
+fn sync_numbers(count: u32) -> Result<(), Overflow> {
+    for i in 0..count {
+        r#yield!(check(i)?);
+    }
+
+    Ok(())
+}
+
+#[fauxgen::generator(yield = u32, try)]
+async fn async_numbers(count: u32) -> Result<(), Overflow> {
+    for i in 0..count {
+        tokio::task::yield_now().await;
+        r#yield!(check(i)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sync_generator() {
+    let gen: GeneratorTryIter<_> = sync_numbers(2);
+    assert_eq!(pin!(gen).collect::<Vec<_>>(), [Ok(0), Ok(1)]);
+
+    let gen = sync_numbers(5);
+    assert_eq!(
+        pin!(gen).collect::<Vec<_>>(),
+        [Ok(0), Ok(1), Ok(2), Err(Overflow)]
+    );
+}
+
+#[test]
+fn stops_after_error() {
+    let mut gen = pin!(sync_numbers(5));
+
+    assert_eq!(gen.by_ref().find(Result::is_err), Some(Err(Overflow)));
+    assert_eq!(gen.size_hint(), (0, Some(0)));
+    assert_eq!(gen.next(), None);
+}
+
+#[test]
+fn size_hint() {
+    #[fauxgen::generator(yield = u32, try, size_hint = (2, Some(2)))]
+    fn two() -> Result<(), Overflow> {
+        r#yield!(1);
+        r#yield!(2);
+        Ok(())
+    }
+
+    // The error adds one more item to the upper bound.
+    let gen = pin!(two());
+    assert_eq!(gen.size_hint(), (2, Some(3)));
+}
+
+#[tokio::test]
+async fn async_generator() {
+    let gen: GeneratorTryStream<_> = async_numbers(5);
+    assert_eq!(
+        gen.collect::<Vec<_>>().await,
+        [Ok(0), Ok(1), Ok(2), Err(Overflow)]
+    );
+}
+
+#[test]
+fn inline_try_gen() {
+    let gen = fauxgen::try_gen! { yield = u32 -> Result<(), Overflow>;
+        for i in 0..5 {
+            r#yield!(check(i)?);
+        }
+    };
+
+    assert_eq!(
+        pin!(gen).collect::<Vec<_>>(),
+        [Ok(0), Ok(1), Ok(2), Err(Overflow)]
+    );
+}
+
+#[test]
+fn inferred_error_type() {
+    let gen = fauxgen::try_gen! { yield = u32;
+        r#yield!(check(1)?);
+    };
+
+    let result: Result<Vec<u32>, Overflow> = pin!(gen).collect();
+    assert_eq!(result, Ok(vec![1]));
+}
+
+#[tokio::test]
+async fn inline_try_stream() {
+    let gen = fauxgen::try_stream! { yield = u32 -> Result<(), Overflow>;
+        for i in 0..5 {
+            tokio::task::yield_now().await;
+            r#yield!(check(i)?);
+        }
+    };
+
+    assert_eq!(
+        gen.collect::<Vec<_>>().await,
+        [Ok(0), Ok(1), Ok(2), Err(Overflow)]
+    );
+}
+
+#[test]
+fn try_closure() {
+    let gen = fauxgen::closure!(
+        #[generator(yield = u32, try)]
+        |count: u32| -> Result<(), Overflow> {
+            for i in 0..count {
+                r#yield!(check(i)?);
+            }
+            Ok(())
+        }
+    );
+
+    assert_eq!(pin!(gen(2)).collect::<Vec<_>>(), [Ok(0), Ok(1)]);
+}
@@ -0,0 +1,8 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, arg = u32)]
+fn with_arg() {
+    yield_batch!(0..10);
+}
+
+fn main() {}
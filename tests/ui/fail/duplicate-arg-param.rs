@@ -0,0 +1,11 @@
+use fauxgen::generator;
+
+#[generator(yield = u32)]
+fn twice(#[arg] _first: u32, #[arg] _second: u32) {}
+
+#[generator(yield = u32, try)]
+fn with_try(#[arg] _first: u32) -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
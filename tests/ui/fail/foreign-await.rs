@@ -0,0 +1,24 @@
+#![deny(deprecated)]
+
+use fauxgen::generator;
+
+async fn fetch() -> u32 {
+    1
+}
+
+#[generator(yield = u32)]
+fn sync_gen() {
+    let value = fetch().await;
+    r#yield!(value);
+}
+
+fn main() {
+    let _ = fauxgen::gen_block! { yield = u32;
+        r#yield!(fetch().await);
+    };
+
+    // Async generators can await anything.
+    let _ = fauxgen::gen_block! { async yield = u32;
+        r#yield!(fetch().await);
+    };
+}
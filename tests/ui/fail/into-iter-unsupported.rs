@@ -0,0 +1,33 @@
+struct Tree;
+
+#[fauxgen::into_iter(yield = u32)]
+impl IntoIterator for Tree {
+    async fn into_iter(self) {}
+}
+
+#[fauxgen::into_iter(yield = u32, arg = u32)]
+impl Tree {
+    fn values(&self) {}
+}
+
+#[fauxgen::into_iter(yield = u32)]
+impl Tree {
+    fn first(&self) {}
+    fn second(&self) {}
+}
+
+#[fauxgen::into_stream(yield = u32)]
+impl Tree {
+    fn values_stream(&self) -> u32 {
+        0
+    }
+}
+
+struct Pair<'a, 'b>(&'a [u32], &'b [u32]);
+
+#[fauxgen::into_iter(yield = u32)]
+impl<'a, 'b> IntoIterator for Pair<'a, 'b> {
+    fn into_iter(self) {}
+}
+
+fn main() {}
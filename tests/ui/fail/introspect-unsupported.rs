@@ -0,0 +1,9 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, introspect(timing))]
+fn unknown_option() {}
+
+#[generator(yield = u32, recursive, introspect)]
+fn recursive() {}
+
+fn main() {}
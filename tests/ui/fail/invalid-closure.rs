@@ -0,0 +1,7 @@
+fn main() {
+    let _gen = fauxgen::closure!(|| {
+        r#yield!(1);
+    });
+
+    let _gen = fauxgen::closure!(#[generator(recursive)] || {});
+}
@@ -0,0 +1,33 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, arg = u32)]
+fn after_yield() {
+    r#yield!(1);
+    let _ = argument!();
+}
+
+#[generator(yield = u32, arg = u32)]
+fn in_loop() {
+    loop {
+        let value = argument!();
+        r#yield!(value);
+    }
+}
+
+#[generator(yield = u32, arg = u32)]
+fn after_branch(flag: bool) {
+    if flag {
+        r#yield!(1);
+    }
+    let _ = argument!();
+}
+
+fn main() {
+    let _ = fauxgen::closure!(
+        #[generator(yield = u32, arg = u32)]
+        || {
+            yield 1;
+            let _ = argument!();
+        }
+    );
+}
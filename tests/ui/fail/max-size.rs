@@ -0,0 +1,23 @@
+use fauxgen::generator;
+
+#[generator(yield = u8, max_size = 256)]
+fn large() {
+    let buffer = [0u8; 1024];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+// The state is on the heap so the generator is small.
+#[generator(yield = u8, max_size = 256, boxed_state)]
+fn boxed() {
+    let buffer = [0u8; 1024];
+    for byte in buffer {
+        r#yield!(byte);
+    }
+}
+
+fn main() {
+    let _ = large();
+    let _ = boxed();
+}
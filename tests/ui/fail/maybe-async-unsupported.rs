@@ -0,0 +1,19 @@
+use fauxgen::generator;
+
+macro_rules! delay {
+    ($($tokens:tt)*) => {};
+}
+
+#[generator(yield = u32, maybe_async)]
+fn unsupported() {
+    r#yield!(std::future::ready(1).await);
+
+    let _ = async { 2 };
+
+    delay!(ready => ready.await);
+}
+
+#[generator(yield = u32, recursive, maybe_async)]
+fn recursive() {}
+
+fn main() {}
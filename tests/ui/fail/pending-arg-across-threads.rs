@@ -0,0 +1,14 @@
+use std::rc::Rc;
+
+#[fauxgen::generator(yield = u32, arg = Rc<u32>)]
+async fn gen() {
+    let _ = argument!();
+}
+
+fn spawn<T: Send + 'static>(value: T) {
+    std::thread::spawn(move || drop(value));
+}
+
+fn main() {
+    spawn(gen());
+}
@@ -0,0 +1,8 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, send)]
+fn send_fn() {}
+
+fn main() {
+    let _ = fauxgen::gen_block! { yield = u32, send; };
+}
@@ -0,0 +1,10 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, arg = u32, try)]
+fn with_arg() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {
+    let _ = fauxgen::try_gen! { async yield = u32; };
+}
@@ -0,0 +1,6 @@
+use fauxgen::generator;
+
+#[generator(allow(late_arguments))]
+fn gen() {}
+
+fn main() {}
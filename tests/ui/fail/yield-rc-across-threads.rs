@@ -0,0 +1,14 @@
+use std::rc::Rc;
+
+#[fauxgen::generator(yield = Rc<u32>)]
+async fn gen() {
+    r#yield!(Rc::new(5));
+}
+
+fn spawn<T: Send + 'static>(value: T) {
+    std::thread::spawn(move || drop(value));
+}
+
+fn main() {
+    spawn(gen());
+}
@@ -2,12 +2,12 @@
 #![deny(deprecated)]
 
 macro_rules! delay {
-    ($value:expr) => { () };
+    ($($tokens:tt)*) => { () };
 }
 
 #[fauxgen::generator]
 fn gen() {
-    let _ = delay!(yield);
+    let _ = delay!(yield => 1);
 }
 
 fn main() {}
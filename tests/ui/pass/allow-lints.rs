@@ -0,0 +1,41 @@
+use fauxgen::generator;
+
+#[generator(yield = u32, allow(foreign_await))]
+fn ready_await() {
+    let value = std::future::ready(1).await;
+    r#yield!(value);
+}
+
+#[generator(yield = u32, arg = u32, allow(late_argument))]
+fn late_argument() {
+    r#yield!(1);
+    let _ = argument!();
+}
+
+// The argument is only read on paths that haven't yielded.
+#[generator(yield = u32, arg = u32)]
+fn separate_branches(flag: bool) {
+    if flag {
+        r#yield!(1);
+    } else {
+        let _ = argument!();
+    }
+}
+
+#[generator(yield = u32, arg = u32)]
+fn separate_arms(flag: bool) {
+    match flag {
+        true => r#yield!(2),
+        false => argument!(),
+    };
+}
+
+// Closures and async blocks are not part of the generator body.
+#[generator(yield = u32)]
+fn nested_futures() {
+    let _ = || async { std::future::ready(1).await };
+    let _ = async { std::future::ready(1).await };
+    r#yield!(1);
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// An `.await` within a sync generator is only a warning, so a future that is
+// immediately ready still compiles without `allow(foreign_await)`.
+#[fauxgen::generator(yield = u32)]
+fn ready_await() {
+    let value = std::future::ready(1).await;
+    r#yield!(value);
+}
+
+fn main() {
+    let values: Vec<_> = std::pin::pin!(ready_await()).collect();
+    assert_eq!(values, [1]);
+}
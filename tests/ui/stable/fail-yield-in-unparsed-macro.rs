@@ -0,0 +1,16 @@
+#![deny(deprecated)]
+
+macro_rules! delay {
+    ($($tokens:tt)*) => { () };
+}
+
+#[fauxgen::generator]
+fn gen() {
+    // These are expanded since the arguments parse as expressions.
+    let _ = delay!(yield, yield 1);
+
+    // This isn't, since it doesn't.
+    let _ = delay!(yield => 1);
+}
+
+fn main() {}
@@ -0,0 +1,90 @@
+//! The `yield` keyword can't be used directly on stable, but rustc doesn't
+//! parse the arguments of a macro until it is expanded. This means that these
+//! work on stable as long as fauxgen expands the `yield` expressions within
+//! them.
+
+use std::pin::pin;
+
+use fauxgen::{Generator, GeneratorState};
+
+fn drive<G>(gen: G, args: &[u32]) -> (Vec<u32>, G::Return)
+where
+    G: Generator<u32, Yield = u32>,
+{
+    let mut gen = pin!(gen);
+    let mut yielded = Vec::new();
+
+    for &arg in args {
+        match gen.as_mut().resume(arg) {
+            GeneratorState::Yielded(value) => yielded.push(value),
+            GeneratorState::Complete(value) => return (yielded, value),
+        }
+    }
+
+    panic!("generator did not complete")
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_vec() -> Vec<u32> {
+    vec![yield 1, yield 2]
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_vec_repeat() -> Vec<u32> {
+    vec![yield 1; 3]
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_format() -> String {
+    format!("{} {second}", yield 1, second = yield 2)
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_assert() {
+    assert_eq!(yield 1, 10, "unexpected argument {}", 10);
+    assert!(matches!(yield 2, 20 | 21));
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_matches() -> bool {
+    matches!(yield 1, value if value == yield 2)
+}
+
+macro_rules! sum {
+    ($( $value:expr ),*) => { 0 $( + $value )* };
+}
+
+#[fauxgen::generator(yield = u32, arg = u32)]
+fn in_user_macro() -> u32 {
+    sum!(yield 1, yield 2, 100)
+}
+
+#[test]
+fn vec() {
+    assert_eq!(drive(in_vec(), &[0, 10, 20]), (vec![1, 2], vec![10, 20]));
+    assert_eq!(drive(in_vec_repeat(), &[0, 7]), (vec![1], vec![7, 7, 7]));
+}
+
+#[test]
+fn format() {
+    assert_eq!(
+        drive(in_format(), &[0, 3, 4]),
+        (vec![1, 2], "3 4".to_string())
+    );
+}
+
+#[test]
+fn asserts() {
+    assert_eq!(drive(in_assert(), &[0, 10, 21]), (vec![1, 2], ()));
+}
+
+#[test]
+fn matches() {
+    assert_eq!(drive(in_matches(), &[0, 5, 5]), (vec![1, 2], true));
+    assert_eq!(drive(in_matches(), &[0, 5, 6]), (vec![1, 2], false));
+}
+
+#[test]
+fn user_macro() {
+    assert_eq!(drive(in_user_macro(), &[0, 1, 2]), (vec![1, 2], 103));
+}